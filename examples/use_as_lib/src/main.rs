@@ -25,17 +25,14 @@ fn main() {
     // load file
     let raw = cleaner::load_file(in_file).unwrap();
 
-    // parse file's content
-    let doc = cleaner::parse_data(&raw[..], &parse_opt).unwrap();
+    // parse, clean and write file's content
+    let (buf, res) = cleaner::clean_data(&raw[..], &parse_opt, &write_opt, &cleaning_opt).unwrap();
 
-    // clean document
-    cleaner::clean_doc(&doc, &cleaning_opt, &write_opt).unwrap();
-
-    // allocate a buffer for the output data
-    let mut buf = Vec::with_capacity(raw.len());
-
-    // write document to buffer
-    cleaner::write_buffer(&doc, &write_opt, &mut buf);
+    // check that cleaned data is smaller
+    if res.is_bigger {
+        println!("Cleaned file is bigger than original.");
+        return;
+    }
 
     // save buffer
     cleaner::save_file(&buf[..], out_file).unwrap();
@@ -0,0 +1,222 @@
+/****************************************************************************
+**
+** svgcleaner could help you to clean up your SVG files
+** from unnecessary data.
+** Copyright (C) 2012-2017 Evgeniy Reizner
+**
+** This program is free software; you can redistribute it and/or modify
+** it under the terms of the GNU General Public License as published by
+** the Free Software Foundation; either version 2 of the License, or
+** (at your option) any later version.
+**
+** This program is distributed in the hope that it will be useful,
+** but WITHOUT ANY WARRANTY; without even the implied warranty of
+** MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+** GNU General Public License for more details.
+**
+** You should have received a copy of the GNU General Public License along
+** with this program; if not, write to the Free Software Foundation, Inc.,
+** 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+**
+****************************************************************************/
+
+
+//! Utilities for a multiple files processing.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// An SVG file found by `collect_files`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct InputFile {
+    pub path: PathBuf,
+    /// A path relative to the input directory.
+    ///
+    /// Contains only the file name when the file was set explicitly.
+    pub relative: PathBuf,
+}
+
+impl InputFile {
+    /// Returns an output path inside the `out_dir`, which keeps the input directory tree.
+    ///
+    /// A compressed output always has an `.svgz` extension.
+    pub fn output_path(&self, out_dir: &Path, compress: bool) -> PathBuf {
+        let path = out_dir.join(&self.relative);
+        if compress { svgz_path(&path) } else { path }
+    }
+}
+
+/// Checks that a path has an `.svg` or `.svgz` extension.
+pub fn is_svg_path(path: &Path) -> bool {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => ext.eq_ignore_ascii_case("svg") || ext.eq_ignore_ascii_case("svgz"),
+        None => false,
+    }
+}
+
+/// Checks that a path has an `.svgz` extension.
+pub fn is_svgz_path(path: &Path) -> bool {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => ext.eq_ignore_ascii_case("svgz"),
+        None => false,
+    }
+}
+
+/// Returns a path with the `.svgz` extension.
+pub fn svgz_path(path: &Path) -> PathBuf {
+    path.with_extension("svgz")
+}
+
+/// Collects SVG files from the list of files and directories.
+///
+/// Directories are processed recursively. Non-SVG files inside directories are skipped,
+/// but explicitly set files are always returned.
+///
+/// Files are returned in the input order. Directory entries are sorted by name.
+///
+/// Each directory is visited only once, so symlink loops are not followed.
+pub fn collect_files<P: AsRef<Path>>(paths: &[P]) -> io::Result<Vec<InputFile>> {
+    let mut list = Vec::new();
+    let mut visited = HashSet::new();
+
+    for path in paths {
+        let path = path.as_ref();
+        if path.is_dir() {
+            collect_dir(path, path, &mut visited, &mut list)?;
+        } else {
+            let name = match path.file_name() {
+                Some(name) => PathBuf::from(name),
+                None => path.to_path_buf(),
+            };
+
+            list.push(InputFile {
+                path: path.to_path_buf(),
+                relative: name,
+            });
+        }
+    }
+
+    Ok(list)
+}
+
+/// Finds two files that would be written to the same output path.
+///
+/// `compress` should be the same as the one passed to `InputFile::output_path`.
+pub fn find_output_collision(files: &[InputFile], compress: bool)
+                             -> Option<(&InputFile, &InputFile)> {
+    let mut list: Vec<(PathBuf, &InputFile)> = files.iter().map(|f| {
+        (f.output_path(Path::new(""), compress), f)
+    }).collect();
+    // stable sort keeps the input order of the colliding files
+    list.sort_by(|a, b| a.0.cmp(&b.0));
+
+    list.windows(2)
+        .find(|w| w[0].0 == w[1].0)
+        .map(|w| (w[0].1, w[1].1))
+}
+
+fn collect_dir(root: &Path, dir: &Path, visited: &mut HashSet<PathBuf>,
+               list: &mut Vec<InputFile>) -> io::Result<()> {
+    if !visited.insert(fs::canonicalize(dir)?) {
+        return Ok(());
+    }
+
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        entries.push(entry?.path());
+    }
+
+    // 'read_dir' order is platform-dependent
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            collect_dir(root, &path, visited, list)?;
+        } else if is_svg_path(&path) {
+            // unwrap is safe, because a path is always inside the root
+            let relative = path.strip_prefix(root).unwrap().to_path_buf();
+            list.push(InputFile {
+                path: path,
+                relative: relative,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn is_svg_path_1() {
+        assert_eq!(is_svg_path(Path::new("a/b.svg")), true);
+        assert_eq!(is_svg_path(Path::new("b.SVG")), true);
+        assert_eq!(is_svg_path(Path::new("b.svgz")), true);
+        assert_eq!(is_svg_path(Path::new("svg")), false);
+        assert_eq!(is_svgz_path(Path::new("b.SVGZ")), true);
+        assert_eq!(is_svgz_path(Path::new("b.svg")), false);
+    }
+
+    #[test]
+    fn collect_files_1() {
+        let root = env::temp_dir().join("svgcleaner_collect_files_1");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("b/c")).unwrap();
+        for name in &["a.svg", "a.png", "b/c/d.svg", "b/a.svg"] {
+            fs::File::create(root.join(name)).unwrap();
+        }
+
+        let list = collect_files(&[root.clone(), PathBuf::from("x/file.svg")]).unwrap();
+        let list: Vec<_> = list.iter().map(|f| f.relative.clone()).collect();
+        assert_eq!(list, vec![
+            PathBuf::from("a.svg"),
+            PathBuf::from("b/a.svg"),
+            PathBuf::from("b/c/d.svg"),
+            PathBuf::from("file.svg"),
+        ]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn collect_files_2() {
+        let list = collect_files(&["a/icon.svg", "b/icon.svg", "b/other.svg"]).unwrap();
+        let (a, b) = find_output_collision(&list, false).unwrap();
+        assert_eq!(a.path, PathBuf::from("a/icon.svg"));
+        assert_eq!(b.path, PathBuf::from("b/icon.svg"));
+
+        let list = collect_files(&["a/icon.svg", "b/other.svg"]).unwrap();
+        assert_eq!(find_output_collision(&list, false), None);
+
+        // both are written to 'icon.svgz' when compressed
+        let list = collect_files(&["a/icon.svg", "a/icon.svgz"]).unwrap();
+        assert_eq!(find_output_collision(&list, false), None);
+        assert!(find_output_collision(&list, true).is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn collect_files_3() {
+        use std::os::unix::fs::symlink;
+
+        let root = env::temp_dir().join("svgcleaner_collect_files_3");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("a")).unwrap();
+        fs::File::create(root.join("a/b.svg")).unwrap();
+        // a/loop -> root
+        symlink(&root, root.join("a/loop")).unwrap();
+
+        let list = collect_files(&[root.clone()]).unwrap();
+        let list: Vec<_> = list.iter().map(|f| f.relative.clone()).collect();
+        assert_eq!(list, vec![PathBuf::from("a/b.svg")]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
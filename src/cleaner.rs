@@ -22,194 +22,424 @@
 
 use std::fs;
 use std::io::{Read, Write};
-use std::io;
+use std::path::Path;
 
-use svgdom;
-use svgdom::{Document, ParseOptions, WriteOptions, WriteBuffer, ElementId};
+use flate2;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
 
-use svgdom_utils;
+use svgdom::{Document, ParseOptions, WriteOptions, WriteBuffer};
 
 use options::Options;
-use task::*;
+use pipeline::{Pipeline, PassReport};
+use task;
 use error;
 
-pub fn load_file(path: &str) -> Result<Vec<u8>, io::Error> {
+/// Information about the `clean_data` call.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CleaningResult {
+    /// Amount of the cleaning passes that were executed.
+    ///
+    /// Always 1 without `Options::multipass`.
+    pub passes: u32,
+    /// Size of the input data in bytes.
+    pub input_size: usize,
+    /// Size of the cleaned data in bytes.
+    pub output_size: usize,
+    /// Indicates that the cleaned data is bigger than the input one.
+    ///
+    /// The cleaned data is still returned, but it should not be used.
+    pub is_bigger: bool,
+}
+
+/// Loads a file.
+///
+/// Compressed files, like SVGZ, will be decompressed automatically.
+pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, error::Error> {
     let mut file = fs::File::open(path)?;
     let length = file.metadata()?.len() as usize;
 
     let mut v = Vec::with_capacity(length + 1);
     file.read_to_end(&mut v)?;
 
+    if is_gzip(&v) {
+        return decompress_data(&v);
+    }
+
     Ok(v)
 }
 
-pub fn parse_data(data: &[u8], opt: &ParseOptions) -> Result<Document, svgdom::Error> {
-    Document::from_data_with_opt(data, opt)
+/// Same as `load_file`, but reads data from any reader, like `io::stdin()`.
+pub fn load_stream<R: Read>(mut reader: R) -> Result<Vec<u8>, error::Error> {
+    let mut v = Vec::new();
+    reader.read_to_end(&mut v)?;
+
+    if is_gzip(&v) {
+        return decompress_data(&v);
+    }
+
+    Ok(v)
 }
 
-pub fn clean_doc(doc: &Document, options: &Options, opt: &WriteOptions)
-                 -> Result<(), error::Error> {
-    preclean_checks(doc)?;
+/// Checks that data is compressed using gzip by the magic bytes.
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&[0x1f, 0x8b])
+}
 
-    // NOTE: Order is important.
-    //       Methods should not depend on each other, but for performance reasons
-    //       they should be executed in this order.
+/// Decompresses gzip data.
+pub fn decompress_data(data: &[u8]) -> Result<Vec<u8>, error::Error> {
+    let mut decoder = GzDecoder::new(data);
 
-    // Prepare our document.
-    // This methods is not optional.
-    utils::resolve_gradient_attributes(doc)?;
-    svgdom_utils::resolve_inherit(doc)?;
-    fix_invalid_attributes(doc);
-    group_defs(doc);
+    // SVG files are usually compressed quite well
+    let mut v = Vec::with_capacity(data.len() * 4);
+    decoder.read_to_end(&mut v)?;
 
-    // Manipulate with tree structure.
-    // Do not remove any attributes before this methods
-    // since they uses them.
+    Ok(v)
+}
 
-    if options.remove_title {
-        remove_element(doc, ElementId::Title);
-    }
+/// Compresses data using gzip.
+///
+/// `level` is in a 0..9 range, where 9 is the best compression.
+pub fn compress_data(data: &[u8], level: u32) -> Result<Vec<u8>, error::Error> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::new(level));
+    encoder.write_all(data)?;
+    let v = encoder.finish()?;
 
-    if options.remove_desc {
-        remove_element(doc, ElementId::Desc);
-    }
+    Ok(v)
+}
 
-    if options.remove_metadata {
-        remove_element(doc, ElementId::Metadata);
-    }
+pub fn parse_data(data: &[u8], opt: &ParseOptions) -> Result<Document, error::Error> {
+    let doc = Document::from_data_with_opt(data, opt)?;
+    Ok(doc)
+}
 
-    if options.remove_unused_defs {
-        remove_unused_defs(doc);
-    }
+/// Returns all problems that prevent the document cleaning, and warnings.
+///
+/// Unlike `clean_doc`, which stops on the first problem.
+pub fn check_doc(doc: &Document, options: &Options) -> Vec<error::Diagnostic> {
+    task::collect_diagnostics(doc, options)
+}
 
-    if options.remove_invalid_stops {
-        remove_invalid_stops(doc);
-    }
+/// Same as `check_doc`, but also parses the data and sets elements positions.
+pub fn check_data(data: &[u8], parse_opt: &ParseOptions, options: &Options)
+                  -> Result<Vec<error::Diagnostic>, error::Error> {
+    let doc = parse_data(data, parse_opt)?;
+    Ok(check_parsed_data(&doc, data, options))
+}
 
-    if options.apply_transform_to_gradients {
-        // Apply transform to gradients before processing to simplify duplicates
-        // detecting and merging.
-        apply_transforms::apply_transform_to_gradients(doc);
+/// Same as `check_data`, but uses an already parsed document.
+///
+/// `data` must contain the data the document was parsed from.
+///
+/// Unlike `check_doc`, also reports problems that can be found only in the source data,
+/// like style sheets in documents with scripts.
+pub fn check_parsed_data(doc: &Document, data: &[u8], options: &Options)
+                         -> Vec<error::Diagnostic> {
+    let mut list = check_doc(doc, options);
+
+    if let Some(e) = task::check_style_sheets(doc, data, options) {
+        list.push(error::Diagnostic {
+            severity: error::Severity::Error,
+            error: e,
+        });
     }
 
-    if options.remove_dupl_linear_gradients {
-        remove_dupl_linear_gradients(doc);
+    for d in &mut list {
+        d.error.resolve_pos(data);
     }
 
-    if options.remove_dupl_radial_gradients {
-        remove_dupl_radial_gradients(doc);
-    }
+    list
+}
 
-    if options.remove_dupl_fe_gaussian_blur {
-        remove_dupl_fe_gaussian_blur(doc);
-    }
+/// Cleans the document using the built-in passes.
+pub fn clean_doc(doc: &Document, options: &Options, opt: &WriteOptions)
+                 -> Result<(), error::Error> {
+    Pipeline::default().run(doc, options, opt)
+}
 
-    if options.merge_gradients {
-        merge_gradients(doc);
-    }
+/// Same as `clean_doc`, but also returns statistics for each executed pass.
+///
+/// See `Pipeline::run_with_report` for details.
+pub fn clean_doc_with_report(doc: &Document, options: &Options, opt: &WriteOptions,
+                             measure_size: bool) -> Result<Vec<PassReport>, error::Error> {
+    Pipeline::default().run_with_report(doc, options, opt, measure_size, 1)
+}
 
-    if options.apply_transform_to_gradients {
-        // Do it again, because something may changed after gradient processing.
-        apply_transforms::apply_transform_to_gradients(doc);
-    }
+/// Parses, cleans and writes SVG data.
+///
+/// This is a shorthand for the `parse_data`, `clean_doc` and `write_buffer` methods,
+/// which also repeats cleaning while the output is shrinking when `Options::multipass`
+/// is enabled.
+///
+/// Unlike `clean_doc`, errors caused by an element will contain its position
+/// in the input data.
+pub fn clean_data(data: &[u8], parse_opt: &ParseOptions, write_opt: &WriteOptions,
+                  options: &Options) -> Result<(Vec<u8>, CleaningResult), error::Error> {
+    clean_data_with_pipeline(data, parse_opt, write_opt, options, &Pipeline::default())
+}
 
-    if options.apply_transform_to_shapes {
-        // Apply before 'convert_shapes_to_paths'.
-        apply_transforms::apply_transform_to_shapes(doc);
-    }
+/// Same as `clean_data`, but uses a custom passes pipeline.
+pub fn clean_data_with_pipeline(data: &[u8], parse_opt: &ParseOptions, write_opt: &WriteOptions,
+                                options: &Options, pipeline: &Pipeline)
+                                -> Result<(Vec<u8>, CleaningResult), error::Error> {
+    _clean_data(data, parse_opt, write_opt, options, pipeline, None)
+}
 
-    if options.convert_shapes {
-        convert_shapes_to_paths(doc);
-    }
+/// Same as `clean_data_with_pipeline`, but also returns statistics for each executed pass.
+///
+/// See `Pipeline::run_with_report` for details.
+pub fn clean_data_with_report(data: &[u8], parse_opt: &ParseOptions, write_opt: &WriteOptions,
+                              options: &Options, pipeline: &Pipeline, measure_size: bool)
+                              -> Result<(Vec<u8>, CleaningResult, Vec<PassReport>), error::Error> {
+    let mut reports = Vec::new();
+    let (buf, res) = _clean_data(data, parse_opt, write_opt, options, pipeline,
+                                 Some((&mut reports, measure_size)))?;
+    Ok((buf, res, reports))
+}
 
-    // NOTE: run before `remove_invisible_elements`, because this method can remove all
-    //       segments from the path which makes it invisible.
-    if options.paths_to_relative {
-        // we only process path's segments if 'PathsToRelative' is enabled
-        paths::process_paths(doc, options);
-    }
+/// Same as `clean_data_with_pipeline`, but cleans an already parsed document,
+/// so it can be checked by `check_doc` first, without parsing the data twice.
+///
+/// `data` must contain the data the document was parsed from.
+///
+/// When `reports` is set, statistics for each executed pass, including the size change,
+/// are stored into it.
+pub fn clean_parsed_data(doc: &Document, data: &[u8], write_opt: &WriteOptions,
+                         options: &Options, pipeline: &Pipeline,
+                         reports: Option<&mut Vec<PassReport>>)
+                         -> Result<(Vec<u8>, CleaningResult), error::Error> {
+    _clean_parsed_data(doc, data, write_opt, options, pipeline, reports.map(|r| (r, true)))
+}
 
-    if options.remove_invisible_elements {
-        remove_invisible_elements(doc);
-    }
+fn _clean_data(data: &[u8], parse_opt: &ParseOptions, write_opt: &WriteOptions,
+               options: &Options, pipeline: &Pipeline,
+               report: Option<(&mut Vec<PassReport>, bool)>)
+               -> Result<(Vec<u8>, CleaningResult), error::Error> {
+    let doc = parse_data(data, parse_opt)?;
+    _clean_parsed_data(&doc, data, write_opt, options, pipeline, report)
+}
 
-    if options.regroup_gradient_stops {
-        regroup_gradient_stops(doc);
+fn _clean_parsed_data(doc: &Document, data: &[u8], write_opt: &WriteOptions,
+                      options: &Options, pipeline: &Pipeline,
+                      mut report: Option<(&mut Vec<PassReport>, bool)>)
+                      -> Result<(Vec<u8>, CleaningResult), error::Error> {
+    if let Some(e) = task::check_style_sheets(doc, data, options) {
+        return Err(e);
     }
 
-    if options.ungroup_groups {
-        ungroup_groups(doc);
+    // allocate a buffer for the output data
+    let capacity = (data.len() as f64 * 0.8) as usize;
+    let mut buf = Vec::with_capacity(capacity);
+    let mut prev_size = 0;
+    let mut passes = 0;
+
+    loop {
+        // clear buffer
+        buf.clear();
+
+        passes += 1;
+        let run_res = match report {
+            Some((ref mut reports, measure_size)) => {
+                pipeline.run_with_report(doc, options, write_opt, measure_size, passes)
+                        .map(|list| reports.extend(list))
+            }
+            None => pipeline.run(doc, options, write_opt),
+        };
+
+        if let Err(mut e) = run_res {
+            e.resolve_pos(data);
+            return Err(e);
+        }
+
+        write_buffer(doc, write_opt, &mut buf);
+
+        if !options.multipass {
+            // do not repeat without 'multipass'
+            break;
+        }
+
+        // if the size is unchaged - exit from the loop
+        if prev_size == buf.len() {
+            break;
+        }
+
+        prev_size = buf.len();
     }
 
-    if options.resolve_use {
-        resolve_use(doc);
-    }
+    let res = CleaningResult {
+        passes: passes,
+        input_size: data.len(),
+        output_size: buf.len(),
+        is_bigger: buf.len() > data.len(),
+    };
 
-    // now we can remove any unneeded attributes
+    Ok((buf, res))
+}
 
-    if options.remove_default_attributes {
-        remove_default_attributes(doc);
-    }
+pub fn write_buffer(doc: &Document, opt: &WriteOptions, buf: &mut Vec<u8>) {
+    doc.write_buf_opt(opt, buf);
+}
 
-    if options.remove_text_attributes {
-        remove_text_attributes(doc);
-    }
+pub fn save_file<P: AsRef<Path>>(data: &[u8], path: P) -> Result<(), error::Error> {
+    let f = fs::File::create(&path)?;
+    save_stream(data, f)
+}
 
-    if options.remove_needless_attributes {
-        remove_needless_attributes(doc);
-    }
+/// Same as `save_file`, but writes data to any writer, like `io::stdout()`.
+pub fn save_stream<W: Write>(data: &[u8], mut writer: W) -> Result<(), error::Error> {
+    writer.write_all(data)?;
+    writer.flush()?;
 
-    if options.remove_gradient_attributes {
-        remove_gradient_attributes(doc);
-    }
+    Ok(())
+}
 
-    if options.remove_unused_coordinates {
-        remove_unused_coordinates(doc);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{ParseOptions, WriteOptions};
+    use options::Options;
+    use pipeline::Pipeline;
+
+    #[test]
+    fn clean_data_1() {
+        let mut opt = Options::default();
+        opt.remove_title = true;
+
+        let data = b"<svg><title>Title</title></svg>";
+        let (buf, res) = clean_data(data, &ParseOptions::default(), &WriteOptions::default(),
+                                    &opt).unwrap();
+
+        assert_eq!(res.passes, 1);
+        assert_eq!(res.input_size, data.len());
+        assert_eq!(res.output_size, buf.len());
+        assert_eq!(res.is_bigger, false);
     }
 
-    // Run only after attributes processed, because
-    // there is no point in grouping default/unneeded attributes.
-    if options.group_by_style {
-        group_by_style(doc);
+    #[test]
+    fn clean_data_multipass() {
+        let mut opt = Options::default();
+        opt.remove_title = true;
+        opt.multipass = true;
+
+        let data = b"<svg><title>Title</title></svg>";
+        let (_, res) = clean_data(data, &ParseOptions::default(), &WriteOptions::default(),
+                                  &opt).unwrap();
+
+        // the second pass doesn't change anything
+        assert_eq!(res.passes, 2);
     }
 
-    // final fixes
-    // list of things that can't break anything
+    #[test]
+    fn clean_data_bigger() {
+        let mut write_opt = WriteOptions::default();
+        write_opt.indent = 4;
 
-    if options.remove_unreferenced_ids {
-        remove_unreferenced_ids(doc);
+        // indention will make the output bigger
+        let data = b"<svg><rect/></svg>";
+        let (_, res) = clean_data(data, &ParseOptions::default(), &write_opt,
+                                  &Options::default()).unwrap();
+
+        assert_eq!(res.is_bigger, true);
     }
 
-    if options.trim_ids {
-        trim_ids(doc);
+    #[test]
+    fn clean_data_report() {
+        let mut opt = Options::default();
+        opt.remove_title = true;
+        opt.multipass = true;
+
+        let data = b"<svg><title>Title</title></svg>";
+        let (_, res, reports) = clean_data_with_report(data, &ParseOptions::default(),
+                                                       &WriteOptions::default(), &opt,
+                                                       &Pipeline::default(), false).unwrap();
+
+        assert_eq!(res.passes, 2);
+
+        let titles: Vec<_> = reports.iter().filter(|r| r.name == "remove-title").collect();
+        assert_eq!(titles.len(), 2);
+        assert_eq!(titles[0].iteration, 1);
+        assert_eq!(titles[0].elements_removed, 1);
+        assert_eq!(titles[0].size_change, None);
+        assert_eq!(titles[1].iteration, 2);
+        assert_eq!(titles[1].elements_removed, 0);
     }
 
-    if options.remove_version {
-        remove_version(doc);
+    #[test]
+    fn clean_data_err() {
+        let res = clean_data(b"<svg><script/></svg>", &ParseOptions::default(),
+                             &WriteOptions::default(), &Options::default());
+        assert_eq!(res.err().unwrap().to_string(), "Scripting is not supported: <script> at 1:6");
     }
 
-    if options.ungroup_defs {
-        ungroup_defs(doc);
+    #[test]
+    fn clean_data_err_pos() {
+        let data = b"<svg>\n  <g>\n    <set id='anim'/>\n  </g>\n</svg>";
+        let res = clean_data(data, &ParseOptions::default(), &WriteOptions::default(),
+                             &Options::default());
+
+        let err = res.err().unwrap();
+        let elem = err.element().unwrap();
+        assert_eq!(elem.tag_name, "set");
+        assert_eq!(elem.id, "anim");
+        assert_eq!(elem.pos, Some(error::TextPos { row: 3, col: 5 }));
     }
 
-    remove_empty_defs(doc);
-    fix_xmlns_attribute(doc, options.remove_xmlns_xlink_attribute);
+    #[test]
+    fn clean_data_err_style() {
+        let data = b"<svg>\n  <style>.a{fill:red}</style>\n  \
+                     <rect class='a' onclick='f()'/>\n</svg>";
+        let mut opt = Options::default();
+        opt.allow_scripts = true;
+        let res = clean_data(data, &ParseOptions::default(), &WriteOptions::default(), &opt);
+
+        let err = res.err().unwrap();
+        assert_eq!(err.code(), "style-sheet-not-supported");
+        assert_eq!(err.element().unwrap().pos, Some(error::TextPos { row: 2, col: 3 }));
+    }
 
-    // NOTE: must be run at last, since it breaks the linking.
-    if options.join_style_attributes {
-        join_style_attributes(doc, opt);
+    #[test]
+    fn check_data_1() {
+        let data = b"<svg>\n  <script/>\n  <set/>\n</svg>";
+        let list = check_data(data, &ParseOptions::default(), &Options::default()).unwrap();
+
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].is_blocking(), true);
+        assert_eq!(list[0].error.element().unwrap().pos, Some(error::TextPos { row: 2, col: 3 }));
+        assert_eq!(list[1].error.element().unwrap().pos, Some(error::TextPos { row: 3, col: 3 }));
     }
 
-    Ok(())
-}
+    #[test]
+    fn stream_1() {
+        let data = b"<svg><title>Title</title></svg>";
+        let raw = load_stream(&data[..]).unwrap();
+        assert_eq!(raw, data.to_vec());
 
-pub fn write_buffer(doc: &Document, opt: &WriteOptions, buf: &mut Vec<u8>) {
-    doc.write_buf_opt(opt, buf);
-}
+        let mut opt = Options::default();
+        opt.remove_title = true;
+        let (buf, _) = clean_data(&raw, &ParseOptions::default(), &WriteOptions::default(),
+                                  &opt).unwrap();
 
-pub fn save_file(data: &[u8], path: &str) -> Result<(), io::Error> {
-    let mut f = fs::File::create(&path)?;
-    f.write_all(&data)?;
+        let mut out = Vec::new();
+        save_stream(&buf, &mut out).unwrap();
+        assert_eq!(out, buf);
+    }
 
-    Ok(())
+    #[test]
+    fn gzip_1() {
+        let data = b"<svg><title>Title</title></svg>";
+        let compressed = compress_data(data, 9).unwrap();
+        assert_eq!(is_gzip(&compressed), true);
+        assert_eq!(is_gzip(data), false);
+
+        assert_eq!(decompress_data(&compressed).unwrap(), data.to_vec());
+        assert_eq!(load_stream(&compressed[..]).unwrap(), data.to_vec());
+    }
+
+    #[test]
+    fn load_file_err() {
+        match load_file("/does/not/exist.svg") {
+            Err(error::Error::Io(_)) => {}
+            _ => panic!("expected an io error"),
+        }
+    }
 }
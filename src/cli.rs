@@ -20,370 +20,369 @@
 **
 ****************************************************************************/
 
-use std::ops::Index;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use clap::{Arg, App, ArgMatches};
+use toml;
 
 use svgdom::{ParseOptions, WriteOptions};
 
-use options::Options;
-
-#[derive(Clone,Copy)]
-pub enum Key {
-    RemoveComments,
-    RemoveDeclarations,
-    RemoveNonsvgElements,
-    RemoveUnusedDefs,
-    ConvertShapes,
-    RemoveTitle,
-    RemoveDesc,
-    RemoveMetadata,
-    RemoveDuplLinearGradients,
-    RemoveDuplRadialGradients,
-    RemoveDuplFeGaussianBlur,
-    UngroupGroups,
-    UngroupDefs,
-    GroupByStyle,
-    MergeGradients,
-    RegroupGradientStops,
-    RemoveInvalidStops,
-    RemoveInvisibleElements,
-    ResolveUse,
-
-    RemoveVersion,
-    RemoveNonsvgAttributes,
-    RemoveUnreferencedIds,
-    TrimIds,
-    RemoveTextAttributes,
-    RemoveUnusedCoordinates,
-    RemoveDefaultAttributes,
-    RemoveXmlnsXlinkAttribute,
-    RemoveNeedlessAttributes,
-    RemoveGradientAttributes,
-    JoinStyleAttributes,
-    ApplyTransformToGradients,
-    ApplyTransformToShapes,
-    RemoveUnresolvedClasses,
-
-    PathsToRelative,
-    RemoveUnusedSegments,
-    ConvertSegments,
-    TrimPaths,
-    JoinArcToFlags,
-    RemoveDuplCmdInPaths,
-    UseImplicitCommands,
-
-    TrimColors,
-    SimplifyTransforms,
-    PathsCoordinatesPrecision,
-    Indent,
-
-    Multipass,
-    CopyOnError,
-    Quiet,
-}
+use config::{self, Config};
+use options::{self, Options, Preset, OptionKind, Tolerance, OPTIONS};
 
-pub struct KeysData<'a>(&'a [&'a str]);
+pub use options::{Key, KeysData, KEYS};
 
-impl<'a> Index<Key> for KeysData<'a> {
-    type Output = &'a str;
+pub fn prepare_app<'a, 'b>() -> App<'a, 'b> {
+    // NOTE: We use custom help output, because `clap` doesn't support
+    //       args grouping.
 
-    fn index(&self, _index: Key) -> &&'a str {
-        &self.0[_index as usize]
+    let mut app = App::new("svgcleaner")
+        .help(include_str!("../data/help.txt"))
+        .version("0.8.1")
+        // input files and directories followed by the output path,
+        // which is not required with '--check-only' and '--check'
+        .arg(Arg::with_name("paths")
+            .required_unless("print-config")
+            .multiple(true)
+            .index(1))
+        .arg(Arg::with_name("print-config")
+            .long("print-config"))
+        .arg(Arg::with_name("config")
+            .long("config")
+            .value_name("PATH"))
+        .arg(Arg::with_name("preset")
+            .long("preset")
+            .value_name("NAME")
+            .possible_values(&["safe", "default", "aggressive"])
+            .default_value("default"))
+        .arg(Arg::with_name("jobs")
+            .short("j")
+            .long("jobs")
+            .value_name("NUM")
+            .default_value("1")
+            .validator(|v| OptionKind::Number("NUM", 1, 256).check_value(&v)))
+        .arg(Arg::with_name("report")
+            .long("report")
+            .value_name("FORMAT")
+            .possible_values(&["text", "json", "jsonl"])
+            .default_value("text"));
+
+    for info in OPTIONS {
+        let arg = Arg::with_name(info.name)
+            .long(info.name)
+            .default_value(info.default);
+
+        let value_name = match info.kind {
+            OptionKind::Flag => "FLAG",
+            OptionKind::Number(value_name, _, _) => value_name,
+            OptionKind::List(value_name) => value_name,
+            OptionKind::Distance(value_name) => value_name,
+        };
+
+        let arg = arg.value_name(value_name).validator(move |v| info.check_value(&v));
+
+        app = app.arg(arg);
     }
-}
 
-pub static KEYS: &'static KeysData<'static> = &KeysData(&[
-    "remove-comments",
-    "remove-declarations",
-    "remove-nonsvg-elements",
-    "remove-unused-defs",
-    "convert-shapes",
-    "remove-title",
-    "remove-desc",
-    "remove-metadata",
-    "remove-dupl-lineargradient",
-    "remove-dupl-radialgradient",
-    "remove-dupl-fegaussianblur",
-    "ungroup-groups",
-    "ungroup-defs",
-    "group-by-style",
-    "merge-gradients",
-    "regroup-gradient-stops",
-    "remove-invalid-stops",
-    "remove-invisible-elements",
-    "resolve-use",
-
-    "remove-version",
-    "remove-nonsvg-attributes",
-    "remove-unreferenced-ids",
-    "trim-ids",
-    "remove-text-attributes",
-    "remove-unused-coordinates",
-    "remove-default-attributes",
-    "remove-xmlns-xlink-attribute",
-    "remove-needless-attributes",
-    "remove-gradient-attributes",
-    "join-style-attributes",
-    "apply-transform-to-gradients",
-    "apply-transform-to-shapes",
-    "remove-unresolved-classes",
-
-    "paths-to-relative",
-    "remove-unused-segments",
-    "convert-segments",
-    "trim-paths",
-    "join-arcto-flags",
-    "remove-dupl-cmd-in-paths",
-    "use-implicit-cmds",
-
-    "trim-colors",
-    "simplify-transforms",
-    "paths-coordinates-precision",
-    "indent",
-
-    "multipass",
-    "copy-on-error",
-    "quiet",
-]);
-
-macro_rules! gen_flag {
-    ($key:expr, $flag:expr) => (
-        Arg::with_name(KEYS[$key])
-            .long(KEYS[$key])
-            .value_name("FLAG")
-            .default_value($flag)
-            .validator(is_flag)
-    )
+    app
 }
 
-pub fn prepare_app<'a, 'b>() -> App<'a, 'b> {
-    debug_assert!(KEYS.0.len() - 1 == Key::Quiet as usize);
+/// Generates the `--help` output.
+///
+/// The result is stored in `data/help.txt`, because `clap` requires a static string.
+/// Use `tools/docgen` to update it.
+pub fn gen_help() -> String {
+    fn push_line(text: &mut String, arg: &str, descr: &str) {
+        // align descriptions
+        const DESCR_COLUMN: usize = 45;
+
+        text.push_str("    ");
+        text.push_str(arg);
+        for _ in (arg.len() + 4)..DESCR_COLUMN {
+            text.push(' ');
+        }
+        text.push_str(descr);
+        text.push('\n');
+    }
 
-    // NOTE: We use custom help output, because `clap` doesn't support
-    //       args grouping.
+    let mut text = String::new();
+
+    text.push_str("\
+svgcleaner could help you to clean up your SVG files from the unnecessary data.
+
+A complete documentation can be found here: \
+https://github.com/RazrFalcon/svgcleaner/blob/master/docs/svgcleaner.rst
+
+USAGE:
+    svgcleaner [OPTIONS] <in-file> <out-file>
+    svgcleaner [OPTIONS] <in-path>... <out-dir>
+
+FLAGS:
+");
+    push_line(&mut text, "-h, --help", "Prints help information");
+    push_line(&mut text, "-V, --version", "Prints version information");
+    push_line(&mut text, "--print-config", "Prints the effective options as a config file");
+
+    text.push_str("\nOPTIONS:\n");
+    push_line(&mut text, "--preset <NAME>",
+              "Set options defaults [values: safe, default, aggressive] [default: default]");
+    push_line(&mut text, "--config <PATH>",
+              "Load options from a TOML or JSON file [default: .svgcleaner.toml]");
+    push_line(&mut text, "-j, --jobs <NUM>",
+              "Number of files to process in parallel (1..256) [default: 1]");
+    push_line(&mut text, "--report <FORMAT>",
+              "Set the results report format [values: text, json, jsonl] [default: text]");
+
+    let mut prev_group = None;
+    for info in OPTIONS {
+        if prev_group != Some(info.group) {
+            text.push('\n');
+            text.push_str(info.group.title());
+            text.push_str(":\n");
+            prev_group = Some(info.group);
+        }
 
-    App::new("svgcleaner")
-        .help(include_str!("../data/help.txt"))
-        .version("0.8.1")
-        .arg(Arg::with_name("in-file")
-            .required(true)
-            .index(1)
-            .validator(is_svg))
-        .arg(Arg::with_name("out-file")
-            .required(true)
-            .index(2)
-            .validator(is_svg))
-
-        // elements
-        .arg(gen_flag!(Key::RemoveComments, "true"))
-        .arg(gen_flag!(Key::RemoveDeclarations, "true"))
-        .arg(gen_flag!(Key::RemoveNonsvgElements, "true"))
-        .arg(gen_flag!(Key::RemoveUnusedDefs, "true"))
-        .arg(gen_flag!(Key::ConvertShapes, "true"))
-        .arg(gen_flag!(Key::RemoveTitle, "true"))
-        .arg(gen_flag!(Key::RemoveDesc, "true"))
-        .arg(gen_flag!(Key::RemoveMetadata, "true"))
-        .arg(gen_flag!(Key::RemoveDuplLinearGradients, "true"))
-        .arg(gen_flag!(Key::RemoveDuplRadialGradients, "true"))
-        .arg(gen_flag!(Key::RemoveDuplFeGaussianBlur, "true"))
-        .arg(gen_flag!(Key::UngroupGroups, "true"))
-        .arg(gen_flag!(Key::UngroupDefs, "true"))
-        .arg(gen_flag!(Key::GroupByStyle, "true"))
-        .arg(gen_flag!(Key::MergeGradients, "true"))
-        .arg(gen_flag!(Key::RegroupGradientStops, "true"))
-        .arg(gen_flag!(Key::RemoveInvalidStops, "true"))
-        .arg(gen_flag!(Key::RemoveInvisibleElements, "true"))
-        .arg(gen_flag!(Key::ResolveUse, "true"))
-
-        // attributes
-        .arg(gen_flag!(Key::RemoveVersion, "true"))
-        .arg(gen_flag!(Key::RemoveNonsvgAttributes, "true"))
-        .arg(gen_flag!(Key::RemoveUnreferencedIds, "true"))
-        .arg(gen_flag!(Key::TrimIds, "true"))
-        .arg(gen_flag!(Key::RemoveTextAttributes, "true"))
-        .arg(gen_flag!(Key::RemoveUnusedCoordinates, "true"))
-        .arg(gen_flag!(Key::RemoveDefaultAttributes, "true"))
-        .arg(gen_flag!(Key::RemoveXmlnsXlinkAttribute, "true"))
-        .arg(gen_flag!(Key::RemoveNeedlessAttributes, "true"))
-        .arg(gen_flag!(Key::RemoveGradientAttributes, "false"))
-        .arg(gen_flag!(Key::JoinStyleAttributes, "true"))
-        .arg(gen_flag!(Key::ApplyTransformToGradients, "true"))
-        .arg(gen_flag!(Key::ApplyTransformToShapes, "true"))
-        .arg(gen_flag!(Key::RemoveUnresolvedClasses, "true"))
-
-        // paths
-        .arg(gen_flag!(Key::PathsToRelative, "true"))
-        .arg(gen_flag!(Key::RemoveUnusedSegments, "true"))
-        .arg(gen_flag!(Key::ConvertSegments, "true"))
-        .arg(gen_flag!(Key::TrimPaths, "true"))
-        .arg(gen_flag!(Key::JoinArcToFlags, "false"))
-        .arg(gen_flag!(Key::RemoveDuplCmdInPaths, "true"))
-        .arg(gen_flag!(Key::UseImplicitCommands, "true"))
-
-        // output
-        .arg(gen_flag!(Key::TrimColors, "true"))
-        .arg(gen_flag!(Key::SimplifyTransforms, "true"))
-        .arg(Arg::with_name(KEYS[Key::PathsCoordinatesPrecision])
-            .long(KEYS[Key::PathsCoordinatesPrecision])
-            .value_name("VALUE")
-            .validator(is_precision)
-            .default_value("8"))
-        .arg(Arg::with_name(KEYS[Key::Indent])
-            .long(KEYS[Key::Indent])
-            .value_name("INDENT")
-            .validator(is_indent)
-            .default_value("-1"))
-
-        // other
-        .arg(gen_flag!(Key::Multipass, "false"))
-        .arg(gen_flag!(Key::CopyOnError, "false"))
-        .arg(gen_flag!(Key::Quiet, "false"))
+        let (value_name, descr) = match info.kind {
+            OptionKind::Flag => {
+                ("FLAG", format!("{} [default: {}]", info.description, info.default))
+            }
+            OptionKind::Number(value_name, min, max) => {
+                (value_name, format!("{} ({}..{}) [default: {}]",
+                                     info.description, min, max, info.default))
+            }
+            OptionKind::List(value_name) => {
+                (value_name, format!("{} [default: '{}']", info.description, info.default))
+            }
+            OptionKind::Distance(value_name) => {
+                (value_name, format!("{} [default: {}]", info.description, info.default))
+            }
+        };
+
+        push_line(&mut text, &format!("--{} <{}>", info.name, value_name), &descr);
+    }
+
+    text.push_str("\nARGS:\n");
+    push_line(&mut text, "<in-file>", "Input file. Use '-' for stdin");
+    push_line(&mut text, "<out-file>", "Output file. Use '-' for stdout");
+    push_line(&mut text, "<in-path>...",
+              "Input files and directories, which are processed recursively");
+    push_line(&mut text, "<out-dir>", "Output directory");
+
+    text
 }
 
-fn is_svg(val: String) -> Result<(), String> {
-    if val.ends_with(".svg") || val.ends_with(".SVG") {
-        Ok(())
-    } else {
-        Err(String::from("The file format must be SVG."))
+pub fn get_preset(args: &ArgMatches, config: &Config) -> Preset {
+    if args.occurrences_of("preset") == 0 {
+        if let Some(preset) = config.preset {
+            return preset;
+        }
     }
+
+    // unwrap is safe, because the value is already validated by 'clap'
+    Preset::from_name(args.value_of("preset").unwrap()).unwrap()
 }
 
-fn is_indent(val: String) -> Result<(), String> {
-    let n = match val.parse::<i8>() {
-        Ok(v) => v,
-        Err(e) => return Err(format!("{}", e)),
+pub fn get_jobs(args: &ArgMatches) -> usize {
+    value_t!(args, "jobs", usize).unwrap()
+}
+
+/// Loads a config file set by `--config` or the one found in the first input path
+/// directory and its ancestors.
+///
+/// Returns an empty config when no file was found.
+pub fn load_config(args: &ArgMatches) -> Result<Config, String> {
+    if let Some(path) = args.value_of("config") {
+        return Config::from_file(Path::new(path));
+    }
+
+    let path = match args.values_of("paths").and_then(|mut v| v.next()) {
+        Some(path) if path != "-" => PathBuf::from(path),
+        _ => env::current_dir().map_err(|e| e.to_string())?,
     };
 
-    if n >= -1 && n <= 4 {
-        Ok(())
+    // we need an absolute path to check all ancestors
+    let path = fs::canonicalize(&path).unwrap_or(path);
+    let dir = if path.is_dir() {
+        path.as_path()
     } else {
-        Err(String::from("Invalid indent value."))
+        path.parent().unwrap_or(Path::new("."))
+    };
+
+    match config::find_config(dir) {
+        Some(path) => Config::from_file(&path),
+        None => Ok(Config::default()),
     }
 }
 
-fn is_precision(val: String) -> Result<(), String> {
-    let n = match val.parse::<u8>() {
-        Ok(v) => v,
-        Err(e) => return Err(format!("{}", e)),
-    };
+// Checks that an option was set explicitly and not by a default value.
+fn is_set(args: &ArgMatches, key: Key) -> bool {
+    args.occurrences_of(key.name()) != 0
+}
 
-    if n >= 1 && n <= 12 {
-        Ok(())
+// Returns a value that was set explicitly, either by an argument or by a config file.
+//
+// Arguments have a higher priority.
+fn explicit_value<'a>(args: &'a ArgMatches, config: &'a Config, key: Key) -> Option<&'a str> {
+    if is_set(args, key) {
+        args.value_of(key.name())
     } else {
-        Err(String::from("Invalid precision value."))
+        config.value(key)
     }
 }
 
-fn is_flag(val: String) -> Result<(), String> {
-    match val.as_ref() {
-        "true" | "false" => Ok(()),
-        _ => Err(String::from("Invalid flag value.")),
+/// Returns a flag value.
+///
+/// If a flag was not set explicitly - a preset value will be returned.
+pub fn get_flag(args: &ArgMatches, config: &Config, key: Key) -> bool {
+    if let Some(value) = explicit_value(args, config, key) {
+        return value == "true";
     }
+
+    // a preset value is disabled when any of the required options is disabled
+    get_preset(args, config).flag(key)
+        && key.info().depends_on.iter().all(|k| get_flag(args, config, *k))
+}
+
+/// Returns a number value.
+///
+/// If a number was not set explicitly - a default value will be returned.
+pub fn get_number(args: &ArgMatches, config: &Config, key: Key) -> i32 {
+    match explicit_value(args, config, key) {
+        // unwrap is safe, because the value is already validated
+        Some(value) => value.parse().unwrap(),
+        None => key.default_number(),
+    }
+}
+
+/// Returns a list value.
+///
+/// If a list was not set explicitly - a default value will be returned.
+pub fn get_list(args: &ArgMatches, config: &Config, key: Key) -> Vec<String> {
+    let value = explicit_value(args, config, key).unwrap_or(key.info().default);
+    options::split_list(value)
 }
 
-pub fn get_flag(args: &ArgMatches, key: Key) -> bool {
-    value_t!(args, KEYS[key], bool).unwrap()
+/// Returns a distance value.
+///
+/// If a distance was not set explicitly - a default value will be returned.
+pub fn get_tolerance(args: &ArgMatches, config: &Config, key: Key) -> Tolerance {
+    let value = explicit_value(args, config, key).unwrap_or(key.info().default);
+    // unwrap is safe, because the value is already validated
+    Tolerance::parse(value).unwrap()
 }
 
 // I don't know how to check it using `clap`, so here is manual checks.
-pub fn check_values(args: &ArgMatches) -> bool {
+pub fn check_values(args: &ArgMatches, config: &Config) -> bool {
+    for info in OPTIONS {
+        if info.kind != OptionKind::Flag {
+            continue;
+        }
+
+        if explicit_value(args, config, info.key).is_none() || !get_flag(args, config, info.key) {
+            continue;
+        }
 
-    fn check_value(args: &ArgMatches, flag: Key, dep: Key) -> bool {
-        if !get_flag(args, flag) && get_flag(args, dep) {
-            println!("Error: You can use '--{}=true' only with '--{}=true'.",
-                     KEYS[dep], KEYS[flag]);
-            return false;
+        for dep in info.depends_on {
+            if !get_flag(args, config, *dep) {
+                println!("Error: You can use '--{}=true' only with '--{}=true'.",
+                         info.name, dep.name());
+                return false;
+            }
         }
-        true
     }
 
-    // TODO: JoinArcToFlags should be automaticaly disabled if TrimPaths is disabled
+    true
+}
 
-    if !check_value(args, Key::TrimPaths, Key::JoinArcToFlags) {
-        return false;
-    }
+pub fn gen_parse_options(args: &ArgMatches, config: &Config) -> ParseOptions {
+    let flag = |key| get_flag(args, config, key);
 
-    if !check_value(args, Key::PathsToRelative, Key::RemoveUnusedSegments) {
-        return false;
-    }
+    let mut opt = get_preset(args, config).parse_options();
 
-    if !check_value(args, Key::PathsToRelative, Key::ConvertSegments) {
-        return false;
+    opt.parse_comments              = !flag(Key::RemoveComments);
+    opt.parse_declarations          = !flag(Key::RemoveDeclarations);
+    opt.parse_unknown_elements      = !flag(Key::RemoveNonsvgElements);
+    opt.parse_unknown_attributes    = !flag(Key::RemoveNonsvgAttributes);
+    opt.skip_unresolved_classes     =  flag(Key::RemoveUnresolvedClasses);
+
+    // scripts can use classes
+    if flag(Key::AllowScripts) {
+        opt.skip_unresolved_classes = false;
     }
 
-    true
+    opt
 }
 
-pub fn gen_parse_options(args: &ArgMatches) -> ParseOptions {
-    let mut opt = ParseOptions::default();
+pub fn gen_write_options(args: &ArgMatches, config: &Config) -> WriteOptions {
+    let flag = |key| get_flag(args, config, key);
+
+    let mut opt = get_preset(args, config).write_options();
+
+    opt.paths.use_compact_notation          = flag(Key::TrimPaths);
+    opt.paths.remove_duplicated_commands    = flag(Key::RemoveDuplCmdInPaths);
+    opt.paths.join_arc_to_flags             = flag(Key::JoinArcToFlags);
+    opt.paths.use_implicit_lineto_commands  = flag(Key::UseImplicitCommands);
+
+    let paths_precision = get_number(args, config, Key::PathsCoordinatesPrecision);
+    opt.paths.coordinates_precision = paths_precision as u8;
+
+    opt.simplify_transform_matrices = flag(Key::SimplifyTransforms);
 
-    opt.parse_comments              = !get_flag(args, Key::RemoveComments);
-    opt.parse_declarations          = !get_flag(args, Key::RemoveDeclarations);
-    opt.parse_unknown_elements      = !get_flag(args, Key::RemoveNonsvgElements);
-    opt.parse_unknown_attributes    = !get_flag(args, Key::RemoveNonsvgAttributes);
-    opt.skip_unresolved_classes     =  get_flag(args, Key::RemoveUnresolvedClasses);
-    opt.parse_px_unit               = false;
+    opt.trim_hex_colors = flag(Key::TrimColors);
+    opt.indent = get_number(args, config, Key::Indent) as i8;
 
     opt
 }
 
-pub fn gen_write_options(args: &ArgMatches) -> WriteOptions {
-    let mut opt = WriteOptions::default();
+pub fn gen_cleaning_options(args: &ArgMatches, config: &Config) -> Options {
+    let mut opt = Options::from_flags(|key| get_flag(args, config, key));
 
-    opt.paths.use_compact_notation          = get_flag(args, Key::TrimPaths);
-    opt.paths.remove_duplicated_commands    = get_flag(args, Key::RemoveDuplCmdInPaths);
-    opt.paths.join_arc_to_flags             = get_flag(args, Key::JoinArcToFlags);
-    opt.paths.use_implicit_lineto_commands  = get_flag(args, Key::UseImplicitCommands);
-
-    let paths_precision = value_t!(args, KEYS[Key::PathsCoordinatesPrecision], u8).unwrap();
-    opt.paths.coordinates_precision = paths_precision;
+    opt.switch_languages = get_list(args, config, Key::SwitchLanguages);
+    opt.switch_features = get_list(args, config, Key::SwitchFeatures);
+    opt.simplify_paths_tolerance = get_tolerance(args, config, Key::SimplifyPathsTolerance);
+    opt.auto_precision_error = get_tolerance(args, config, Key::AutoPrecisionError);
 
+    opt
+}
 
-    opt.simplify_transform_matrices = get_flag(args, Key::SimplifyTransforms);
+/// Generates a config file with the effective options.
+pub fn gen_config(args: &ArgMatches, config: &Config) -> String {
+    let mut text = format!("preset = \"{}\"\n", get_preset(args, config).name());
 
-    opt.remove_leading_zero = true;
+    let mut prev_group = None;
+    for info in OPTIONS {
+        if prev_group != Some(info.group) {
+            text.push_str(&format!("\n# {}\n", info.group.title()));
+            prev_group = Some(info.group);
+        }
 
-    opt.trim_hex_colors = get_flag(args, Key::TrimColors);
-    opt.indent = value_t!(args, KEYS[Key::Indent], i8).unwrap();
+        let value = match info.kind {
+            OptionKind::Flag => get_flag(args, config, info.key).to_string(),
+            OptionKind::Number(..) => get_number(args, config, info.key).to_string(),
+            OptionKind::List(..) => {
+                toml::Value::String(get_list(args, config, info.key).join(",")).to_string()
+            }
+            OptionKind::Distance(..) => {
+                let value = explicit_value(args, config, info.key).unwrap_or(info.default);
+                toml::Value::String(value.to_string()).to_string()
+            }
+        };
+
+        text.push_str(&format!("{} = {}\n", info.name, value));
+    }
 
-    opt
+    text
 }
 
-pub fn gen_cleaning_options(args: &ArgMatches) -> Options {
-    Options {
-        remove_unused_defs: get_flag(args, Key::RemoveUnusedDefs),
-        convert_shapes: get_flag(args, Key::ConvertShapes),
-        remove_title: get_flag(args, Key::RemoveTitle),
-        remove_desc: get_flag(args, Key::RemoveDesc),
-        remove_metadata: get_flag(args, Key::RemoveMetadata),
-        remove_dupl_linear_gradients: get_flag(args, Key::RemoveDuplLinearGradients),
-        remove_dupl_radial_gradients: get_flag(args, Key::RemoveDuplRadialGradients),
-        remove_dupl_fe_gaussian_blur: get_flag(args, Key::RemoveDuplFeGaussianBlur),
-        ungroup_groups: get_flag(args, Key::UngroupGroups),
-        ungroup_defs: get_flag(args, Key::UngroupDefs),
-        group_by_style: get_flag(args, Key::GroupByStyle),
-        merge_gradients: get_flag(args, Key::MergeGradients),
-        regroup_gradient_stops: get_flag(args, Key::RegroupGradientStops),
-        remove_invalid_stops: get_flag(args, Key::RemoveInvalidStops),
-        remove_invisible_elements: get_flag(args, Key::RemoveInvisibleElements),
-        resolve_use: get_flag(args, Key::ResolveUse),
-
-        remove_version: get_flag(args, Key::RemoveVersion),
-        remove_unreferenced_ids: get_flag(args, Key::RemoveUnreferencedIds),
-        trim_ids: get_flag(args, Key::TrimIds),
-        remove_text_attributes: get_flag(args, Key::RemoveTextAttributes),
-        remove_unused_coordinates: get_flag(args, Key::RemoveUnusedCoordinates),
-        remove_default_attributes: get_flag(args, Key::RemoveDefaultAttributes),
-        remove_xmlns_xlink_attribute: get_flag(args, Key::RemoveXmlnsXlinkAttribute),
-        remove_needless_attributes: get_flag(args, Key::RemoveNeedlessAttributes),
-        remove_gradient_attributes: get_flag(args, Key::RemoveGradientAttributes),
-        join_style_attributes: get_flag(args, Key::JoinStyleAttributes),
-        apply_transform_to_gradients: get_flag(args, Key::ApplyTransformToGradients),
-        apply_transform_to_shapes: get_flag(args, Key::ApplyTransformToShapes),
-
-        paths_to_relative: get_flag(args, Key::PathsToRelative),
-        remove_unused_segments: get_flag(args, Key::RemoveUnusedSegments),
-        convert_segments: get_flag(args, Key::ConvertSegments),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn help_is_up_to_date() {
+        // run 'tools/docgen' to update 'data/help.txt'
+        assert!(gen_help() == include_str!("../data/help.txt"));
     }
 }
@@ -0,0 +1,253 @@
+/****************************************************************************
+**
+** svgcleaner could help you to clean up your SVG files
+** from unnecessary data.
+** Copyright (C) 2012-2017 Evgeniy Reizner
+**
+** This program is free software; you can redistribute it and/or modify
+** it under the terms of the GNU General Public License as published by
+** the Free Software Foundation; either version 2 of the License, or
+** (at your option) any later version.
+**
+** This program is distributed in the hope that it will be useful,
+** but WITHOUT ANY WARRANTY; without even the implied warranty of
+** MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+** GNU General Public License for more details.
+**
+** You should have received a copy of the GNU General Public License along
+** with this program; if not, write to the Free Software Foundation, Inc.,
+** 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+**
+****************************************************************************/
+
+
+//! Config files support.
+//!
+//! A config file is a flat TOML or JSON table, where keys are the CLI arguments names:
+//!
+//! ```toml
+//! preset = "safe"
+//! remove-title = true
+//! indent = 2
+//! ```
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde_json;
+use toml;
+
+use options::{Key, Preset, OptionKind, OPTIONS};
+
+/// A name of the config file that will be looked for in the input file directory
+/// and its ancestors.
+pub const CONFIG_NAME: &'static str = ".svgcleaner.toml";
+
+/// Options loaded from a config file.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Config {
+    pub preset: Option<Preset>,
+    // values are stored as strings, just like the CLI ones
+    values: Vec<(Key, String)>,
+}
+
+// A config value, independent from the file format.
+enum Value {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Unsupported,
+}
+
+impl Config {
+    /// Loads a config file.
+    ///
+    /// Files with a `.json` extension are parsed as JSON and all other as TOML.
+    pub fn from_file(path: &Path) -> Result<Config, String> {
+        let mut text = String::new();
+        let res = fs::File::open(path).and_then(|mut f| f.read_to_string(&mut text));
+        if let Err(e) = res {
+            return Err(format!("Failed to load '{}': {}.", path.display(), e));
+        }
+
+        let is_json = path.extension().and_then(|e| e.to_str()) == Some("json");
+        let res = if is_json { Config::from_json(&text) } else { Config::from_toml(&text) };
+        res.map_err(|e| format!("Invalid config '{}': {}", path.display(), e))
+    }
+
+    pub fn from_toml(text: &str) -> Result<Config, String> {
+        let table = match text.parse::<toml::Value>() {
+            Ok(toml::Value::Table(table)) => table,
+            Ok(_) => return Err(String::from("Root element must be a table.")),
+            Err(e) => return Err(format!("{}", e)),
+        };
+
+        let list = table.into_iter().map(|(k, v)| {
+            let v = match v {
+                toml::Value::Boolean(b) => Value::Bool(b),
+                toml::Value::Integer(n) => Value::Integer(n),
+                toml::Value::Float(n) => Value::Float(n),
+                toml::Value::String(s) => Value::String(s),
+                _ => Value::Unsupported,
+            };
+
+            (k, v)
+        });
+
+        Config::from_values(list)
+    }
+
+    pub fn from_json(text: &str) -> Result<Config, String> {
+        let map = match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(serde_json::Value::Object(map)) => map,
+            Ok(_) => return Err(String::from("Root element must be an object.")),
+            Err(e) => return Err(format!("{}", e)),
+        };
+
+        let list = map.into_iter().map(|(k, v)| {
+            let v = match v {
+                serde_json::Value::Bool(b) => Value::Bool(b),
+                serde_json::Value::Number(n) => {
+                    match (n.as_i64(), n.as_f64()) {
+                        (Some(n), _) => Value::Integer(n),
+                        (None, Some(n)) => Value::Float(n),
+                        _ => Value::Unsupported,
+                    }
+                }
+                serde_json::Value::String(s) => Value::String(s),
+                _ => Value::Unsupported,
+            };
+
+            (k, v)
+        });
+
+        Config::from_values(list)
+    }
+
+    fn from_values<I>(list: I) -> Result<Config, String>
+        where I: Iterator<Item=(String, Value)>
+    {
+        let mut config = Config::default();
+
+        for (name, value) in list {
+            let info = OPTIONS.iter().find(|info| info.name == name);
+
+            // only distances can be fractional
+            let is_distance = match info.map(|info| info.kind) {
+                Some(OptionKind::Distance(..)) => true,
+                _ => false,
+            };
+
+            let value = match value {
+                Value::Bool(b) => b.to_string(),
+                Value::Integer(n) => n.to_string(),
+                Value::Float(n) if is_distance => n.to_string(),
+                Value::String(s) => s,
+                Value::Float(_) | Value::Unsupported => {
+                    return Err(format!("Unsupported value type of the '{}' option.", name));
+                }
+            };
+
+            if name == "preset" {
+                config.preset = match Preset::from_name(&value) {
+                    Some(v) => Some(v),
+                    None => return Err(format!("Invalid preset '{}'.", value)),
+                };
+
+                continue;
+            }
+
+            let info = match info {
+                Some(info) => info,
+                None => return Err(format!("Unknown option '{}'.", name)),
+            };
+
+            if let Err(e) = info.check_value(&value) {
+                return Err(format!("Invalid value of the '{}' option: {}", name, e));
+            }
+
+            config.values.push((info.key, value));
+        }
+
+        Ok(config)
+    }
+
+    /// Returns an option value, if it was set.
+    pub fn value(&self, key: Key) -> Option<&str> {
+        self.values.iter().find(|&&(k, _)| k == key).map(|&(_, ref v)| v.as_str())
+    }
+}
+
+/// Looks for the `CONFIG_NAME` file in the directory and its ancestors.
+pub fn find_config(dir: &Path) -> Option<PathBuf> {
+    let mut dir = Some(dir);
+    while let Some(d) = dir {
+        let path = d.join(CONFIG_NAME);
+        if path.is_file() {
+            return Some(path);
+        }
+
+        dir = d.parent();
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use options::{Key, Preset};
+
+    #[test]
+    fn toml_1() {
+        let config = Config::from_toml("preset = 'safe'\nremove-title = true\nindent = 2").unwrap();
+        assert_eq!(config.preset, Some(Preset::Safe));
+        assert_eq!(config.value(Key::RemoveTitle), Some("true"));
+        assert_eq!(config.value(Key::Indent), Some("2"));
+        assert_eq!(config.value(Key::RemoveDesc), None);
+    }
+
+    #[test]
+    fn toml_float_1() {
+        let config = Config::from_toml("simplify-paths-tolerance = 0.5").unwrap();
+        assert_eq!(config.value(Key::SimplifyPathsTolerance), Some("0.5"));
+        assert_eq!(Config::from_toml("indent = 0.5").unwrap_err(),
+                   "Unsupported value type of the 'indent' option.");
+    }
+
+    #[test]
+    fn json_1() {
+        let config = Config::from_json("{\"remove-title\": false, \"indent\": 4}").unwrap();
+        assert_eq!(config.preset, None);
+        assert_eq!(config.value(Key::RemoveTitle), Some("false"));
+        assert_eq!(config.value(Key::Indent), Some("4"));
+    }
+
+    #[test]
+    fn invalid_1() {
+        assert_eq!(Config::from_toml("remove-all = true").unwrap_err(),
+                   "Unknown option 'remove-all'.");
+        assert_eq!(Config::from_toml("indent = 10").unwrap_err(),
+                   "Invalid value of the 'indent' option: Invalid value.");
+        assert_eq!(Config::from_toml("remove-title = 1.5").unwrap_err(),
+                   "Unsupported value type of the 'remove-title' option.");
+        assert_eq!(Config::from_json("[]").unwrap_err(),
+                   "Root element must be an object.");
+    }
+
+    #[test]
+    fn find_config_1() {
+        let root = env::temp_dir().join("svgcleaner_find_config_1");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::File::create(root.join(CONFIG_NAME)).unwrap();
+
+        assert_eq!(find_config(&root.join("a/b")), Some(root.join(CONFIG_NAME)));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
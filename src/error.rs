@@ -20,36 +20,265 @@
 **
 ****************************************************************************/
 
+
+use std::error;
 use std::fmt;
+use std::io;
 
+use svgdom;
+use svgdom::Node;
 use svgdom_utils;
 
-#[derive(PartialEq)]
+/// Position in the source text.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TextPos {
+    /// A line number, starting from 1.
+    pub row: usize,
+    /// A column number in bytes, starting from 1.
+    pub col: usize,
+}
+
+impl fmt::Display for TextPos {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.row, self.col)
+    }
+}
+
+/// An element that caused an error.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ErrorElement {
+    pub tag_name: String,
+    /// An element `id`. Empty if not set.
+    pub id: String,
+    /// An element position in the source text.
+    ///
+    /// Set only by the `cleaner::clean_data` methods, since a document doesn't store
+    /// nodes positions.
+    pub pos: Option<TextPos>,
+    // An index of the element between elements with the same tag name.
+    index: usize,
+}
+
+impl ErrorElement {
+    /// Creates a new error element from the SVG element.
+    pub fn from_node(node: &Node) -> ErrorElement {
+        let eid = node.tag_id().unwrap();
+
+        let index = node.document().descendants().svg()
+                        .take_while(|n| n != node)
+                        .filter(|n| n.is_tag_name(eid))
+                        .count();
+
+        ErrorElement {
+            tag_name: eid.name().to_owned(),
+            id: node.id().clone(),
+            pos: None,
+            index: index,
+        }
+    }
+
+    /// Creates a new error element from the first element with the specified tag name
+    /// in the source data.
+    ///
+    /// Returns `None` if there is no such element.
+    pub fn find(data: &[u8], tag_name: &str) -> Option<ErrorElement> {
+        find_element_pos(data, tag_name, 0).map(|pos| {
+            ErrorElement {
+                tag_name: tag_name.to_owned(),
+                id: String::new(),
+                pos: Some(pos),
+                index: 0,
+            }
+        })
+    }
+
+    fn resolve_pos(&mut self, data: &[u8]) {
+        self.pos = find_element_pos(data, &self.tag_name, self.index);
+    }
+}
+
+impl fmt::Display for ErrorElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{}", self.tag_name)?;
+
+        if !self.id.is_empty() {
+            write!(f, " id=\"{}\"", self.id)?;
+        }
+
+        write!(f, ">")?;
+
+        if let Some(pos) = self.pos {
+            write!(f, " at {}", pos)?;
+        }
+
+        Ok(())
+    }
+}
+
 pub enum Error {
     UnresolvedAttribute(String), // attribute name
     MissingAttribute(String, String), // tag name, attribute name
-    ScriptingIsNotSupported,
-    AnimationIsNotSupported,
-    ConditionalProcessingIsNotSupported,
-    ExternalHrefIsNotSupported(String), // ref data
+    ScriptingIsNotSupported(ErrorElement),
+    AnimationIsNotSupported(ErrorElement),
+    ConditionalProcessingIsNotSupported(ErrorElement),
+    ExternalHrefIsNotSupported(String, ErrorElement), // ref data
+    RequiredExtensionsIsNotSupported(ErrorElement),
+    StyleSheetIsNotSupported(ErrorElement),
+    ParsingFailed(svgdom::Error),
+    Io(io::Error),
 }
 
-impl fmt::Debug for Error {
+impl Error {
+    /// Returns a short error code, like `scripting-not-supported`.
+    ///
+    /// Codes are stable and can be used by an external code.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Error::UnresolvedAttribute(..) => "unresolved-attribute",
+            Error::MissingAttribute(..) => "missing-attribute",
+            Error::ScriptingIsNotSupported(..) => "scripting-not-supported",
+            Error::AnimationIsNotSupported(..) => "animation-not-supported",
+            Error::ConditionalProcessingIsNotSupported(..) =>
+                "conditional-processing-not-supported",
+            Error::ExternalHrefIsNotSupported(..) => "external-href-not-supported",
+            Error::RequiredExtensionsIsNotSupported(..) => "required-extensions-not-supported",
+            Error::StyleSheetIsNotSupported(..) => "style-sheet-not-supported",
+            Error::ParsingFailed(..) => "parsing-failed",
+            Error::Io(..) => "io-error",
+        }
+    }
+
+    /// Returns an element that caused this error.
+    pub fn element(&self) -> Option<&ErrorElement> {
+        match *self {
+              Error::ScriptingIsNotSupported(ref e)
+            | Error::AnimationIsNotSupported(ref e)
+            | Error::ConditionalProcessingIsNotSupported(ref e)
+            | Error::ExternalHrefIsNotSupported(_, ref e)
+            | Error::RequiredExtensionsIsNotSupported(ref e)
+            | Error::StyleSheetIsNotSupported(ref e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn element_mut(&mut self) -> Option<&mut ErrorElement> {
+        match *self {
+              Error::ScriptingIsNotSupported(ref mut e)
+            | Error::AnimationIsNotSupported(ref mut e)
+            | Error::ConditionalProcessingIsNotSupported(ref mut e)
+            | Error::ExternalHrefIsNotSupported(_, ref mut e)
+            | Error::RequiredExtensionsIsNotSupported(ref mut e)
+            | Error::StyleSheetIsNotSupported(ref mut e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Sets an element position using the source data.
+    pub fn resolve_pos(&mut self, data: &[u8]) {
+        if let Some(e) = self.element_mut() {
+            e.resolve_pos(data);
+        }
+    }
+}
+
+impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Error::UnresolvedAttribute(ref name) =>
                 write!(f, "Failed to resolved attribute '{}'", name),
             Error::MissingAttribute(ref tag_name, ref attr_name) =>
                 write!(f, "The attribute '{}' is missing in the '{}' element", attr_name, tag_name),
-            Error::ScriptingIsNotSupported =>
-                write!(f, "Scripting is not supported"),
-            Error::AnimationIsNotSupported =>
-                write!(f, "Animation is not supported"),
-            Error::ConditionalProcessingIsNotSupported =>
-                write!(f, "Conditional processing attributes is not supported"),
-            Error::ExternalHrefIsNotSupported(ref s) =>
+            Error::ScriptingIsNotSupported(ref e) =>
+                write!(f, "Scripting is not supported: {}", e),
+            Error::AnimationIsNotSupported(ref e) =>
+                write!(f, "Animation is not supported: {}", e),
+            Error::ConditionalProcessingIsNotSupported(ref e) =>
+                write!(f, "Conditional processing attributes is not supported: {}", e),
+            Error::ExternalHrefIsNotSupported(ref s, ref e) =>
                 write!(f, "The 'xlink:href' attribute is referencing an external object '{}', \
-                           which is not supported", s),
+                           which is not supported: {}", s, e),
+            Error::RequiredExtensionsIsNotSupported(ref e) =>
+                write!(f, "The 'requiredExtensions' attribute is not supported \
+                           and will be ignored: {}", e),
+            Error::StyleSheetIsNotSupported(ref e) =>
+                write!(f, "Style sheets are not supported in documents with scripts: {}", e),
+            Error::ParsingFailed(ref e) =>
+                write!(f, "{:?}", e),
+            Error::Io(ref e) =>
+                write!(f, "{}", e),
+        }
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Error) -> bool {
+        match (self, other) {
+            (&Error::UnresolvedAttribute(ref a), &Error::UnresolvedAttribute(ref b)) => a == b,
+            (&Error::MissingAttribute(ref a1, ref a2), &Error::MissingAttribute(ref b1, ref b2)) =>
+                a1 == b1 && a2 == b2,
+            (&Error::ScriptingIsNotSupported(ref a), &Error::ScriptingIsNotSupported(ref b)) =>
+                a == b,
+            (&Error::AnimationIsNotSupported(ref a), &Error::AnimationIsNotSupported(ref b)) =>
+                a == b,
+            (&Error::ConditionalProcessingIsNotSupported(ref a),
+             &Error::ConditionalProcessingIsNotSupported(ref b)) => a == b,
+            (&Error::ExternalHrefIsNotSupported(ref a1, ref a2),
+             &Error::ExternalHrefIsNotSupported(ref b1, ref b2)) => a1 == b1 && a2 == b2,
+            (&Error::RequiredExtensionsIsNotSupported(ref a),
+             &Error::RequiredExtensionsIsNotSupported(ref b)) => a == b,
+            (&Error::StyleSheetIsNotSupported(ref a), &Error::StyleSheetIsNotSupported(ref b)) =>
+                a == b,
+            (&Error::ParsingFailed(ref a), &Error::ParsingFailed(ref b)) => a == b,
+            // 'io::Error' doesn't implement 'PartialEq'
+            (&Error::Io(ref a), &Error::Io(ref b)) => a.kind() == b.kind(),
+            _ => false,
+        }
+    }
+}
+
+/// A severity of the `Diagnostic`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Severity {
+    /// The document can't be cleaned.
+    Error,
+    /// The document can be cleaned, but the result may be incorrect.
+    Warning,
+}
+
+/// A problem found by `cleaner::check_doc`.
+#[derive(PartialEq, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub error: Error,
+}
+
+impl Diagnostic {
+    /// Checks that diagnostic prevents the document cleaning.
+    pub fn is_blocking(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "Error: {}.", self.error),
+            Severity::Warning => write!(f, "Warning: {}.", self.error),
         }
     }
 }
@@ -64,3 +293,131 @@ impl From<svgdom_utils::Error> for Error {
         }
     }
 }
+
+impl From<svgdom::Error> for Error {
+    fn from(value: svgdom::Error) -> Error {
+        Error::ParsingFailed(value)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Error {
+        Error::Io(value)
+    }
+}
+
+// Finds a start of the N-th element with the specified tag name.
+//
+// It's a simple text search, so it will skip only comments, but not CDATA, entities, etc.
+fn find_element_pos(data: &[u8], tag_name: &str, index: usize) -> Option<TextPos> {
+    let tag_name = tag_name.as_bytes();
+    let mut count = 0;
+    let mut i = 0;
+
+    while i < data.len() {
+        if data[i..].starts_with(b"<!--") {
+            match find_sub(&data[i..], b"-->") {
+                Some(p) => i += p + 3,
+                None => return None,
+            }
+            continue;
+        }
+
+        if data[i..].starts_with(b"<![CDATA[") {
+            match find_sub(&data[i..], b"]]>") {
+                Some(p) => i += p + 3,
+                None => return None,
+            }
+            continue;
+        }
+
+        if data[i] != b'<' {
+            i += 1;
+            continue;
+        }
+
+        // skip a namespace prefix, like 'svg:'
+        let name_start = i + 1 + prefix_len(&data[i + 1..]);
+
+        if data[name_start..].starts_with(tag_name) {
+            let end = name_start + tag_name.len();
+            let is_name_end = match data.get(end) {
+                Some(&c) => c == b'>' || c == b'/' || (c as char).is_whitespace(),
+                None => false,
+            };
+
+            if is_name_end {
+                if count == index {
+                    return Some(text_pos(data, i));
+                }
+
+                count += 1;
+            }
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+// Returns the length of the namespace prefix, including the colon.
+fn prefix_len(data: &[u8]) -> usize {
+    let len = data.iter().take_while(|c| c.is_ascii_alphanumeric() || b"_-.".contains(c)).count();
+    if data.get(len) == Some(&b':') { len + 1 } else { 0 }
+}
+
+fn find_sub(data: &[u8], sub: &[u8]) -> Option<usize> {
+    data.windows(sub.len()).position(|w| w == sub)
+}
+
+fn text_pos(data: &[u8], offset: usize) -> TextPos {
+    let before = &data[..offset];
+    let row = before.iter().filter(|c| **c == b'\n').count() + 1;
+    let col = match before.iter().rposition(|c| *c == b'\n') {
+        Some(p) => offset - p,
+        None => offset + 1,
+    };
+
+    TextPos {
+        row: row,
+        col: col,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_pos_1() {
+        let data = b"<svg>\n  <!-- <rect/> -->\n  <rect/>\n  <rect id='r'/>\n</svg>";
+        assert_eq!(find_element_pos(data, "rect", 0), Some(TextPos { row: 3, col: 3 }));
+        assert_eq!(find_element_pos(data, "rect", 1), Some(TextPos { row: 4, col: 3 }));
+        assert_eq!(find_element_pos(data, "rect", 2), None);
+    }
+
+    #[test]
+    fn find_pos_2() {
+        // 'svg' is not 'svgz'
+        let data = b"<svgz/><svg/>";
+        assert_eq!(find_element_pos(data, "svg", 0), Some(TextPos { row: 1, col: 8 }));
+    }
+
+    #[test]
+    fn find_pos_3() {
+        // CDATA is skipped and prefixed elements are found
+        let data = b"<svg>\n<script><![CDATA[ var s = '<rect/>'; ]]></script>\n<svg:rect/>\n</svg>";
+        assert_eq!(find_element_pos(data, "rect", 0), Some(TextPos { row: 3, col: 1 }));
+        assert_eq!(ErrorElement::find(data, "style"), None);
+    }
+
+    #[test]
+    fn code_1() {
+        let e = Error::MissingAttribute("rect".to_string(), "width".to_string());
+        assert_eq!(e.code(), "missing-attribute");
+
+        let e = Error::from(io::Error::new(io::ErrorKind::NotFound, "not found"));
+        assert_eq!(e.code(), "io-error");
+    }
+}
@@ -22,19 +22,30 @@
 
 #[cfg(feature = "cli-parsing")]
 #[macro_use] extern crate clap;
+#[cfg(feature = "cli-parsing")]
+extern crate serde_json;
+#[cfg(feature = "cli-parsing")]
+extern crate toml;
 
+extern crate flate2;
 extern crate svgdom;
 extern crate svgdom_utils;
 
 pub use svgdom::{ParseOptions, WriteOptions};
 
 pub use options::Options as CleaningOptions;
-pub use error::Error;
+pub use options::Preset;
+pub use error::{Error, ErrorElement, TextPos, Diagnostic, Severity};
+pub use pipeline::{CleaningPass, Pipeline, PassReport};
 
 #[cfg(feature = "cli-parsing")]
 pub mod cli;
+#[cfg(feature = "cli-parsing")]
+pub mod config;
 
+pub mod batch;
 pub mod cleaner;
 mod error;
-mod options;
+pub mod options;
+mod pipeline;
 mod task;
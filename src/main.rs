@@ -21,26 +21,226 @@
 ****************************************************************************/
 
 extern crate svgcleaner;
+#[macro_use] extern crate serde_json;
 
+use std::borrow::Cow;
+use std::cmp;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
 
 use svgcleaner::cli;
 use svgcleaner::cli::Key;
 use svgcleaner::cleaner;
+use svgcleaner::batch::{self, InputFile};
+use svgcleaner::{Pipeline, PassReport, ParseOptions, WriteOptions, CleaningOptions, Severity,
+                  Diagnostic};
 
+// Prints an error and exits with the specified code.
 macro_rules! try_msg {
-    ($e:expr) => ({
+    ($e:expr, $code:expr) => ({
         match $e {
             Ok(o) => o,
             Err(e) => {
                 println!("Error: {}.", e);
-                return;
+                process::exit($code);
             }
         }
     })
 }
 
+// Same as 'try_msg', but stores an error in the log instead of printing it.
+macro_rules! try_log {
+    ($log:expr, $e:expr) => ({
+        match $e {
+            Ok(o) => o,
+            Err(e) => {
+                $log.error(e.code(), &e);
+                return Err(());
+            }
+        }
+    })
+}
+
+// Process exit codes. Should be in sync with the README.
+const EXIT_SUCCESS: i32 = 0;
+// Invalid arguments, config or input file type.
+const EXIT_INVALID_ARGS: i32 = 1;
+// Some files are not optimal. Only for '--check'.
+const EXIT_NOT_OPTIMAL: i32 = 2;
+// An input file doesn't exist or a file can't be read or written.
+const EXIT_IO_ERROR: i32 = 3;
+const EXIT_PARSING_FAILED: i32 = 4;
+// An unsupported or invalid SVG content.
+const EXIT_CLEANING_FAILED: i32 = 5;
+const EXIT_BIGGER_OUTPUT: i32 = 6;
+// Cleaning is failed, but the original file was copied because of '--copy-on-error'.
+const EXIT_COPIED: i32 = 7;
+
+// Options that are shared between all files.
+struct Settings {
+    parse_opt: ParseOptions,
+    write_opt: WriteOptions,
+    cleaning_opt: CleaningOptions,
+    copy_on_error: bool,
+    verbose: bool,
+    compress: bool,
+    compression_level: u32,
+}
+
+// Messages and errors codes of a single file.
+#[derive(Default)]
+struct Log {
+    // Messages that should be printed for this file.
+    messages: Vec<String>,
+    errors: Vec<&'static str>,
+    warnings: Vec<&'static str>,
+}
+
+impl Log {
+    fn error<T: ToString + ?Sized>(&mut self, code: &'static str, msg: &T) {
+        self.messages.push(format!("Error: {}.", msg.to_string()));
+        self.errors.push(code);
+    }
+
+    fn warning<T: ToString + ?Sized>(&mut self, code: &'static str, msg: &T) {
+        self.messages.push(format!("Warning: {}.", msg.to_string()));
+        self.warnings.push(code);
+    }
+
+    // Returns an exit code for the first error.
+    fn exit_code(&self) -> i32 {
+        match self.errors.first() {
+            Some(code) => error_exit_code(code),
+            None => EXIT_SUCCESS,
+        }
+    }
+}
+
+// A single file processing result.
+#[derive(Default)]
+struct FileResult {
+    log: Log,
+    output: Option<PathBuf>,
+    // Input and output sizes. Not set on error.
+    sizes: Option<(usize, usize)>,
+    // Amount of the cleaning passes. Zero on error.
+    passes: u32,
+    // The original file was copied to the output path on error.
+    copied: bool,
+}
+
+impl FileResult {
+    fn from_error<T: ToString + ?Sized>(code: &'static str, msg: &T) -> FileResult {
+        let mut res = FileResult::default();
+        res.log.error(code, msg);
+        res
+    }
+
+    fn exit_code(&self) -> i32 {
+        if self.copied {
+            EXIT_COPIED
+        } else {
+            self.log.exit_code()
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ReportFormat {
+    Text,
+    Json,
+    JsonLines,
+}
+
+// Prints files processing results.
+struct Reporter {
+    format: ReportFormat,
+    // Print messages to stderr, so stdout can be used for the data or a JSON report.
+    use_stderr: bool,
+    records: Vec<serde_json::Value>,
+}
+
+impl Reporter {
+    fn new(format: ReportFormat) -> Reporter {
+        Reporter {
+            format: format,
+            use_stderr: format != ReportFormat::Text,
+            records: Vec::new(),
+        }
+    }
+
+    fn is_text(&self) -> bool {
+        self.format == ReportFormat::Text
+    }
+
+    // Prints a text message.
+    fn print(&self, msg: &str) {
+        if self.use_stderr {
+            eprintln!("{}", msg);
+        } else {
+            println!("{}", msg);
+        }
+    }
+
+    // Prints file messages.
+    //
+    // A file path is printed only in the batch mode.
+    fn print_messages(&self, path: Option<&Path>, log: &Log) {
+        if log.messages.is_empty() {
+            return;
+        }
+
+        if let Some(path) = path {
+            self.print(&format!("{}:", path.display()));
+        }
+
+        for msg in &log.messages {
+            self.print(msg);
+        }
+    }
+
+    // Adds a file to the JSON report.
+    fn add_file(&mut self, in_path: &Path, res: &FileResult) {
+        let (in_size, out_size, ratio) = match res.sizes {
+            Some((a, b)) => (Some(a), Some(b), Some(shrink_ratio(a, b))),
+            None => (None, None, None),
+        };
+
+        let record = json!({
+            "input": in_path.to_string_lossy(),
+            "output": res.output.as_ref().map(|p| p.to_string_lossy()),
+            "input_size": in_size,
+            "output_size": out_size,
+            "ratio": ratio,
+            "passes": res.passes,
+            "errors": res.log.errors,
+            "warnings": res.log.warnings,
+        });
+
+        match self.format {
+            ReportFormat::Text => {}
+            ReportFormat::Json => self.records.push(record),
+            ReportFormat::JsonLines => println!("{}", record),
+        }
+    }
+
+    // Prints the whole JSON document.
+    fn finish(&mut self) {
+        if self.format == ReportFormat::Json {
+            let records = serde_json::Value::Array(self.records.drain(..).collect());
+            println!("{}", serde_json::to_string_pretty(&records).unwrap());
+        }
+    }
+}
+
 fn main() {
     let app = cli::prepare_app();
     let args = match app.get_matches_safe() {
@@ -54,96 +254,556 @@ fn main() {
         }
     };
 
-    if !cli::check_values(&args) { return; }
-    let parse_opt = cli::gen_parse_options(&args);
-    let write_opt = cli::gen_write_options(&args);
-    let cleaning_opt = cli::gen_cleaning_options(&args);
+    let config = match cli::load_config(&args) {
+        Ok(c) => c,
+        Err(e) => {
+            println!("Error: {}", e);
+            process::exit(EXIT_INVALID_ARGS);
+        }
+    };
 
-    let in_file  = args.value_of("in-file").unwrap();
-    let out_file = args.value_of("out-file").unwrap();
+    if !cli::check_values(&args, &config) { process::exit(EXIT_INVALID_ARGS); }
 
-    if !Path::new(in_file).exists() {
-        println!("Error: Input file does not exist.");
+    if args.is_present("print-config") {
+        print!("{}", cli::gen_config(&args, &config));
         return;
     }
 
-    // load file
-    let raw = try_msg!(cleaner::load_file(in_file));
+    let settings = Settings {
+        parse_opt: cli::gen_parse_options(&args, &config),
+        write_opt: cli::gen_write_options(&args, &config),
+        cleaning_opt: cli::gen_cleaning_options(&args, &config),
+        copy_on_error: cli::get_flag(&args, &config, Key::CopyOnError),
+        verbose: cli::get_flag(&args, &config, Key::Verbose),
+        compress: cli::get_flag(&args, &config, Key::Compress),
+        compression_level: cli::get_number(&args, &config, Key::CompressionLevel) as u32,
+    };
+
+    let mut reporter = Reporter::new(match args.value_of("report") {
+        Some("json") => ReportFormat::Json,
+        Some("jsonl") => ReportFormat::JsonLines,
+        _ => ReportFormat::Text,
+    });
 
-    let on_err = || {
-        // copy original file to destination
-        if cli::get_flag(&args, Key::CopyOnError) {
-            // copy a file only when paths are different
-            if in_file != out_file {
-                try_msg!(fs::copy(in_file, out_file));
+    let paths: Vec<&str> = args.values_of("paths").unwrap().collect();
+
+    for path in &paths {
+        if !is_stdio(Path::new(path)) && !Path::new(path).exists() {
+            println!("Error: Input file does not exist.");
+            process::exit(EXIT_IO_ERROR);
+        }
+    }
+
+    if cli::get_flag(&args, &config, Key::CheckOnly) {
+        let files = try_msg!(batch::collect_files(&paths), EXIT_IO_ERROR);
+        process::exit(check_files(&settings, &files, &mut reporter));
+    }
+
+    if cli::get_flag(&args, &config, Key::Check) {
+        let files = try_msg!(batch::collect_files(&paths), EXIT_IO_ERROR);
+        let threshold = cli::get_number(&args, &config, Key::CheckThreshold) as f64;
+
+        let mut shrinkable = 0;
+        let mut exit_code = EXIT_SUCCESS;
+        let settings = Arc::new(settings);
+        process_files(files, cli::get_jobs(&args),
+                      move |file| check_optimal_file(&settings, &file.path),
+                      |file, res| {
+                          reporter.print_messages(Some(&file.path), &res.log);
+                          reporter.add_file(&file.path, &res);
+                          exit_code = merge_exit_codes(exit_code, res.exit_code());
+
+                          if let Some((in_size, out_size)) = res.sizes {
+                              let ratio = shrink_ratio(in_size, out_size);
+                              if out_size < in_size && ratio > threshold {
+                                  reporter.print(&format!(
+                                      "Would shrink: {} ({} -> {} bytes, {:.2}%)",
+                                      file.path.display(), in_size, out_size, ratio));
+                                  shrinkable += 1;
+                              }
+                          }
+                      });
+
+        reporter.finish();
+
+        // errors have a priority
+        if exit_code == EXIT_SUCCESS && shrinkable != 0 {
+            exit_code = EXIT_NOT_OPTIMAL;
+        }
+
+        process::exit(exit_code);
+    }
+
+    if paths.len() < 2 {
+        println!("Error: The following required arguments were not provided: <out-file>.");
+        process::exit(EXIT_INVALID_ARGS);
+    }
+
+    // the last path is an output one
+    let (out_path, in_paths) = paths.split_last().unwrap();
+    let out_path = Path::new(out_path);
+
+    // a single file mode is used only when both paths are files
+    if in_paths.len() == 1 && !Path::new(in_paths[0]).is_dir() && !out_path.is_dir() {
+        let in_path = Path::new(in_paths[0]);
+        for path in &[in_path, out_path] {
+            if !is_stdio(path) && !batch::is_svg_path(path) {
+                println!("Error: The file format must be SVG.");
+                process::exit(EXIT_INVALID_ARGS);
             }
         }
 
-        std::process::exit(0);
-    };
+        // a compressed output always has an '.svgz' extension, like in the batch mode
+        let out_path = if settings.compress && !is_stdio(out_path) {
+            batch::svgz_path(out_path)
+        } else {
+            out_path.to_path_buf()
+        };
+        let out_path = out_path.as_path();
 
-    // parse it
-    let doc = match cleaner::parse_data(&raw[..], &parse_opt) {
-        Ok(d) => d,
-        Err(e) => {
-            println!("Error: {:?}.", e);
-            on_err();
-            return;
+        if is_stdio(out_path) {
+            if !reporter.is_text() {
+                println!("Error: A JSON report can't be used with stdout output.");
+                process::exit(EXIT_INVALID_ARGS);
+            }
+
+            // stdout is used for the output data
+            reporter.use_stderr = true;
         }
-    };
 
-    // allocate a buffer for the output data
-    let capacity = (raw.len() as f64 * 0.8) as usize;
-    let mut buf = Vec::with_capacity(capacity);
-    let mut prev_size = 0;
+        let res = clean_file(&settings, in_path, out_path);
+        reporter.print_messages(None, &res.log);
+        reporter.add_file(in_path, &res);
+        reporter.finish();
 
-    loop {
-        // clear buffer
-        buf.clear();
+        if let Some((in_size, out_size)) = res.sizes {
+            if reporter.is_text() && !cli::get_flag(&args, &config, Key::Quiet) {
+                let ratio = shrink_ratio(in_size, out_size);
+                reporter.print(&format!("Your image is {:.2}% smaller now.", ratio));
+            }
+        }
 
-        // clean document
-        match cleaner::clean_doc(&doc, &cleaning_opt, &write_opt) {
-            Ok(_) => {}
-            Err(e) => {
-                println!("Error: {:?}.", e);
-                on_err();
-                break;
+        process::exit(res.exit_code());
+    }
+
+    if is_stdio(out_path) || (out_path.exists() && !out_path.is_dir()) {
+        println!("Error: The output path must be a directory.");
+        process::exit(EXIT_INVALID_ARGS);
+    }
+
+    let files = try_msg!(batch::collect_files(in_paths), EXIT_IO_ERROR);
+    if let Some((a, b)) = batch::find_output_collision(&files, settings.compress) {
+        println!("Error: '{}' and '{}' would be written to the same output file.",
+                 a.path.display(), b.path.display());
+        process::exit(EXIT_INVALID_ARGS);
+    }
+
+    let mut results = Vec::with_capacity(files.len());
+
+    {
+        let settings = Arc::new(settings);
+        let out_dir = out_path.to_path_buf();
+        let reporter = &mut reporter;
+        process_files(files, cli::get_jobs(&args),
+                      move |file| clean_batch_file(&settings, file, &out_dir),
+                      |file, res| {
+                          reporter.print_messages(Some(&file.path), &res.log);
+                          reporter.add_file(&file.path, &res);
+                          results.push(res);
+                      });
+    }
+
+    reporter.finish();
+
+    if reporter.is_text() && !cli::get_flag(&args, &config, Key::Quiet) {
+        print_summary(&results);
+    }
+
+    let exit_code = results.iter()
+        .fold(EXIT_SUCCESS, |code, res| merge_exit_codes(code, res.exit_code()));
+    process::exit(exit_code);
+}
+
+// Processes files using the specified amount of threads.
+//
+// Each thread has its own 'Document', since it can't be shared.
+// 'on_result' is always called in the input files order.
+fn process_files<F, C>(files: Vec<InputFile>, jobs: usize, func: F, mut on_result: C)
+    where F: Fn(&InputFile) -> FileResult + Send + Sync + 'static,
+          C: FnMut(&InputFile, FileResult)
+{
+    if jobs < 2 || files.len() < 2 {
+        for file in &files {
+            let res = func(file);
+            on_result(file, res);
+        }
+
+        return;
+    }
+
+    let files = Arc::new(files);
+    let func = Arc::new(func);
+    let next_idx = Arc::new(AtomicUsize::new(0));
+    let (tx, rx) = mpsc::channel();
+
+    let mut handles = Vec::new();
+    for _ in 0..cmp::min(jobs, files.len()) {
+        let files = files.clone();
+        let func = func.clone();
+        let next_idx = next_idx.clone();
+        let tx = tx.clone();
+
+        handles.push(thread::spawn(move || {
+            loop {
+                let idx = next_idx.fetch_add(1, Ordering::SeqCst);
+                if idx >= files.len() {
+                    break;
+                }
+
+                let res = func(&files[idx]);
+                if tx.send((idx, res)).is_err() {
+                    break;
+                }
             }
+        }));
+    }
+
+    // the channel will be closed when all threads are finished
+    drop(tx);
+
+    // results are received in a random order, so we have to cache them
+    let mut pending = HashMap::new();
+    let mut idx = 0;
+    for (i, res) in rx {
+        pending.insert(i, res);
+
+        while let Some(res) = pending.remove(&idx) {
+            on_result(&files[idx], res);
+            idx += 1;
         }
+    }
+
+    for handle in handles {
+        // a panic message is already printed
+        let _ = handle.join();
+    }
 
-        // write buffer
-        cleaner::write_buffer(&doc, &write_opt, &mut buf);
+    // results of the files that caused a panic are missing
+    while idx < files.len() {
+        let res = pending.remove(&idx).unwrap_or_else(|| {
+            FileResult::from_error("panic", "Failed to process a file")
+        });
+
+        on_result(&files[idx], res);
+        idx += 1;
+    }
+}
 
-        if !cli::get_flag(&args, Key::Multipass) {
-            // do not repeat without '--multipass'
-            break;
+// Prints all problems and not only the first one.
+//
+// Returns an exit code of the first file with a blocking problem.
+fn check_files(settings: &Settings, files: &[InputFile], reporter: &mut Reporter) -> i32 {
+    let mut exit_code = EXIT_SUCCESS;
+    for file in files {
+        let mut res = FileResult::default();
+        if check_path(settings, &file.path, &mut res.log).is_err() {
+            exit_code = merge_exit_codes(exit_code, res.exit_code());
         }
 
-        // if the size is unchaged - exit from the loop
-        if prev_size == buf.len() {
-            break;
+        let path = if files.len() == 1 { None } else { Some(file.path.as_path()) };
+        reporter.print_messages(path, &res.log);
+        reporter.add_file(&file.path, &res);
+    }
+
+    reporter.finish();
+
+    exit_code
+}
+
+fn check_path(settings: &Settings, path: &Path, log: &mut Log) -> Result<(), ()> {
+    let raw = try_log!(log, load_input(path));
+    let data = try_log!(log, decompress_input(&raw));
+    check_file(settings, &data, log)
+}
+
+fn check_file(settings: &Settings, data: &[u8], log: &mut Log) -> Result<(), ()> {
+    let list = try_log!(log, cleaner::check_data(data, &settings.parse_opt,
+                                                     &settings.cleaning_opt));
+    log_diagnostics(&list, log)
+}
+
+// Logs all diagnostics and returns an error if any of them is blocking.
+fn log_diagnostics(list: &[Diagnostic], log: &mut Log) -> Result<(), ()> {
+    for d in list {
+        match d.severity {
+            Severity::Error => log.error(d.error.code(), &d.error),
+            Severity::Warning => log.warning(d.error.code(), &d.error),
         }
+    }
 
-        prev_size = buf.len();
+    if list.iter().any(|d| d.is_blocking()) {
+        return Err(());
     }
 
+    Ok(())
+}
+
+fn clean_batch_file(settings: &Settings, file: &InputFile, out_dir: &Path) -> FileResult {
+    if !batch::is_svg_path(&file.path) {
+        return FileResult::from_error("not-svg", "The file format must be SVG");
+    }
+
+    let out_path = file.output_path(out_dir, settings.compress);
+
+    // keep the input directory tree
+    if let Some(dir) = out_path.parent() {
+        if let Err(e) = fs::create_dir_all(dir) {
+            return FileResult::from_error("io-error", &e);
+        }
+    }
+
+    clean_file(settings, &file.path, &out_path)
+}
+
+fn clean_file(settings: &Settings, in_path: &Path, out_path: &Path) -> FileResult {
+    let raw = match load_input(in_path) {
+        Ok(v) => v,
+        Err(e) => return FileResult::from_error(e.code(), &e),
+    };
+
+    let data = match decompress_input(&raw) {
+        Ok(v) => v,
+        Err(e) => return FileResult::from_error(e.code(), &e),
+    };
+
+    let mut res = FileResult::default();
+    res.output = Some(out_path.to_path_buf());
+
+    let compress = settings.compress || batch::is_svgz_path(out_path);
+    match _clean_file(settings, &raw, &data, out_path, compress, &mut res.log) {
+        Ok((sizes, passes)) => {
+            res.sizes = Some(sizes);
+            res.passes = passes;
+        }
+        Err(_) => {
+            // copy original data to destination
+            // only when paths are different
+            if settings.copy_on_error && (in_path != out_path || is_stdio(out_path)) {
+                match copy_input(settings, &raw, out_path, compress) {
+                    Ok(_) => res.copied = true,
+                    Err(e) => res.log.error(e.code(), &e),
+                }
+            } else {
+                res.output = None;
+            }
+        }
+    }
+
+    res
+}
+
+// Saves the original data, compressed the same way as the cleaned one would be.
+fn copy_input(settings: &Settings, raw: &[u8], out_path: &Path, compress: bool)
+              -> Result<(), svgcleaner::Error> {
+    if compress && !cleaner::is_gzip(raw) {
+        let data = cleaner::compress_data(raw, settings.compression_level)?;
+        save_output(&data, out_path)
+    } else {
+        save_output(raw, out_path)
+    }
+}
+
+// 'raw' is the original, possibly compressed, file and 'data' is the decompressed one.
+//
+// Returns the input and output sizes and the amount of passes.
+fn _clean_file(settings: &Settings, raw: &[u8], data: &[u8], out_path: &Path, compress: bool,
+               log: &mut Log) -> Result<((usize, usize), u32), ()> {
+    let (buf, sizes, is_bigger, passes) = clean_buffer(settings, raw, data, compress, log)?;
+
     // check that cleaned file is smaller
-    if buf.len() > raw.len() {
-        println!("Error: Cleaned file is bigger than original.");
-        on_err();
-        return;
+    if is_bigger {
+        log.error("bigger-output", "Cleaned file is bigger than original");
+        return Err(());
     }
 
     // save buffer
-    try_msg!(cleaner::save_file(&buf[..], out_file));
+    try_log!(log, save_output(&buf[..], out_path));
 
-    if cli::get_flag(&args, Key::Quiet) {
-        return;
+    Ok((sizes, passes))
+}
+
+// Checks, cleans and compresses, if needed, the data.
+//
+// The data is parsed only once: the document is checked for problems and then cleaned.
+//
+// Input and output sizes are measured the same way: when the output is compressed,
+// it's compared with the compressed input.
+//
+// Returns the result data, the input and output sizes, a flag that indicates
+// that it's bigger than the original one and the amount of passes.
+fn clean_buffer(settings: &Settings, raw: &[u8], data: &[u8], compress: bool,
+                log: &mut Log) -> Result<(Vec<u8>, (usize, usize), bool, u32), ()> {
+    let doc = try_log!(log, cleaner::parse_data(data, &settings.parse_opt));
+
+    let list = cleaner::check_parsed_data(&doc, data, &settings.cleaning_opt);
+    log_diagnostics(&list, log)?;
+
+    let mut reports = Vec::new();
+    let res = cleaner::clean_parsed_data(&doc, data, &settings.write_opt, &settings.cleaning_opt,
+                                         &Pipeline::default(),
+                                         if settings.verbose { Some(&mut reports) } else { None });
+    let (buf, res) = try_log!(log, res);
+
+    if settings.verbose {
+        log.messages.push(format_report(&reports));
+    }
+
+    if !compress {
+        return Ok((buf, (data.len(), buf.len()), res.is_bigger, res.passes));
+    }
+
+    let out = try_log!(log, cleaner::compress_data(&buf, settings.compression_level));
+
+    let in_size = if cleaner::is_gzip(raw) {
+        raw.len()
+    } else {
+        try_log!(log, cleaner::compress_data(raw, settings.compression_level)).len()
+    };
+
+    let sizes = (in_size, out.len());
+    Ok((out, sizes, sizes.1 > sizes.0, res.passes))
+}
+
+// Cleans a file without saving it.
+//
+// Stores the original and the cleaned data sizes.
+fn check_optimal_file(settings: &Settings, path: &Path) -> FileResult {
+    let mut res = FileResult::default();
+    if let Ok((sizes, passes)) = _check_optimal_file(settings, path, &mut res.log) {
+        res.sizes = Some(sizes);
+        res.passes = passes;
+    }
+
+    res
+}
+
+fn _check_optimal_file(settings: &Settings, path: &Path, log: &mut Log)
+                       -> Result<((usize, usize), u32), ()> {
+    if !is_stdio(path) && !batch::is_svg_path(path) {
+        log.error("not-svg", "The file format must be SVG");
+        return Err(());
+    }
+
+    let raw = try_log!(log, load_input(path));
+    let data = try_log!(log, decompress_input(&raw));
+
+    let compress = settings.compress || batch::is_svgz_path(path);
+    let (_, sizes, _, passes) = clean_buffer(settings, &raw, &data, compress, log)?;
+
+    Ok((sizes, passes))
+}
+
+fn error_exit_code(code: &str) -> i32 {
+    match code {
+        "not-svg" => EXIT_INVALID_ARGS,
+        "io-error" => EXIT_IO_ERROR,
+        "parsing-failed" => EXIT_PARSING_FAILED,
+        "bigger-output" => EXIT_BIGGER_OUTPUT,
+        _ => EXIT_CLEANING_FAILED,
+    }
+}
+
+// Keeps the first error, but an error has a priority over a copied file.
+fn merge_exit_codes(prev: i32, code: i32) -> i32 {
+    if prev == EXIT_SUCCESS || (prev == EXIT_COPIED && code != EXIT_SUCCESS) {
+        code
+    } else {
+        prev
+    }
+}
+
+// '-' is used for stdin and stdout.
+fn is_stdio(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+// Unlike 'cleaner::load_file', doesn't decompress the data,
+// because we need the original one for '--copy-on-error'.
+fn load_input(path: &Path) -> Result<Vec<u8>, svgcleaner::Error> {
+    let mut data = Vec::new();
+
+    if is_stdio(path) {
+        let stdin = io::stdin();
+        let mut lock = stdin.lock();
+        lock.read_to_end(&mut data)?;
+    } else {
+        let mut file = fs::File::open(path)?;
+        file.read_to_end(&mut data)?;
+    }
+
+    Ok(data)
+}
+
+fn decompress_input(data: &[u8]) -> Result<Cow<[u8]>, svgcleaner::Error> {
+    if cleaner::is_gzip(data) {
+        cleaner::decompress_data(data).map(Cow::Owned)
+    } else {
+        Ok(Cow::Borrowed(data))
+    }
+}
+
+fn save_output(data: &[u8], path: &Path) -> Result<(), svgcleaner::Error> {
+    if is_stdio(path) {
+        let stdout = io::stdout();
+        let lock = stdout.lock();
+        cleaner::save_stream(data, lock)
+    } else {
+        cleaner::save_file(data, path)
+    }
+}
+
+fn print_summary(results: &[FileResult]) {
+    let mut cleaned = 0;
+    let mut in_size = 0;
+    let mut out_size = 0;
+    for res in results {
+        if let Some((a, b)) = res.sizes {
+            cleaned += 1;
+            in_size += a;
+            out_size += b;
+        }
+    }
+
+    let saved = in_size as i64 - out_size as i64;
+    let ratio = shrink_ratio(in_size, out_size);
+
+    println!("{:<12} {:>10}", "Files:", results.len());
+    println!("{:<12} {:>10}", "Cleaned:", cleaned);
+    println!("{:<12} {:>10}", "Failed:", results.len() - cleaned);
+    println!("{:<12} {:>10}", "Input size:", in_size);
+    println!("{:<12} {:>10}", "Output size:", out_size);
+    println!("{:<12} {:>10} ({:.2}%)", "Saved:", saved, ratio);
+}
+
+// Returns how much the output is smaller than the input, in percent.
+fn shrink_ratio(in_size: usize, out_size: usize) -> f64 {
+    if in_size == 0 {
+        return 0.0;
+    }
+
+    100.0 - out_size as f64 / in_size as f64 * 100.0
+}
+
+fn format_report(reports: &[PassReport]) -> String {
+    let mut text = format!("{:<4} {:<32} {:>8} {:>10} {:>6} {:>8} {:>10}",
+                           "Iter", "Pass", "Elements", "Attributes", "Moved", "Size", "Time (ms)");
+
+    for r in reports {
+        let time = r.time.as_secs() as f64 * 1000.0 + r.time.subsec_nanos() as f64 / 1000000.0;
+        text.push_str(&format!("\n{:<4} {:<32} {:>8} {:>10} {:>6} {:>8} {:>10.3}",
+                               r.iteration, r.name, r.elements_removed, r.attributes_removed,
+                               r.nodes_moved, r.size_change.unwrap_or(0), time));
     }
 
-    // unwrap is safe, because 'save_file' will fail on write error,
-    // so file is totally exist
-    let out_size = fs::File::open(out_file).unwrap().metadata().unwrap().len() as f64;
-    let ratio = 100.0 - out_size / (raw.len() as f64) * 100.0;
-    println!("Your image is {:.2}% smaller now.", ratio);
+    text
 }
@@ -20,79 +20,621 @@
 **
 ****************************************************************************/
 
-/// Documentation can be found in: docs/svgcleaner.rst
-pub struct Options {
-    pub remove_unused_defs: bool,
-    pub convert_shapes: bool,
-    pub remove_title: bool,
-    pub remove_desc: bool,
-    pub remove_metadata: bool,
-    pub remove_dupl_linear_gradients: bool,
-    pub remove_dupl_radial_gradients: bool,
-    pub remove_dupl_fe_gaussian_blur: bool,
-    pub ungroup_groups: bool,
-    pub ungroup_defs: bool,
-    pub group_by_style: bool,
-    pub merge_gradients: bool,
-    pub regroup_gradient_stops: bool,
-    pub remove_invalid_stops: bool,
-    pub remove_invisible_elements: bool,
-    pub resolve_use: bool,
-
-    pub remove_version: bool,
-    pub remove_unreferenced_ids: bool,
-    pub trim_ids: bool,
-    pub remove_text_attributes: bool,
-    pub remove_unused_coordinates: bool,
-    pub remove_default_attributes: bool,
-    pub remove_xmlns_xlink_attribute: bool,
-    pub remove_needless_attributes: bool,
-    pub remove_gradient_attributes: bool,
-    pub join_style_attributes: bool,
-    pub apply_transform_to_gradients: bool,
-    pub apply_transform_to_shapes: bool,
-
-    pub paths_to_relative: bool,
-    pub remove_unused_segments: bool,
-    pub convert_segments: bool,
+
+//! The cleaning options registry.
+//!
+//! Each option is declared only once in the `options!` invocation below.
+//! `Key`, `KEYS`, `OPTIONS` and `Options` are generated from it,
+//! and the CLI arguments, `data/help.txt` and the documentation are generated from them.
+
+use std::ops::Index;
+
+use svgdom::{ParseOptions, WriteOptions};
+
+use self::OptionKind::{Flag, Number, List, Distance};
+
+/// Options group.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Group {
+    Elements,
+    Attributes,
+    Paths,
+    Output,
+    Other,
+}
+
+impl Group {
+    /// Returns a group title as used in the `--help` output and in the documentation.
+    pub fn title(&self) -> &'static str {
+        match *self {
+            Group::Elements => "Elements",
+            Group::Attributes => "Attributes",
+            Group::Paths => "Paths",
+            Group::Output => "Output",
+            Group::Other => "Other",
+        }
+    }
+}
+
+/// Option value type.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OptionKind {
+    /// `true` or `false`.
+    Flag,
+    /// An integer. Contains a value name, a minimum and a maximum values.
+    Number(&'static str, i32, i32),
+    /// A comma-separated list of strings. Contains a value name.
+    List(&'static str),
+    /// A non-negative number in user units or a percentage of the viewport size,
+    /// like `0.5` or `0.1%`. Contains a value name.
+    Distance(&'static str),
+}
+
+/// Option description.
+pub struct OptionInfo {
+    pub key: Key,
+    /// A name of the CLI argument.
+    pub name: &'static str,
+    pub group: Group,
+    pub kind: OptionKind,
+    /// A default value, as used by the CLI.
+    pub default: &'static str,
+    /// Options that must be enabled to enable this one.
+    pub depends_on: &'static [Key],
+    pub description: &'static str,
+}
+
+impl OptionKind {
+    /// Checks that a string is a valid value of this kind.
+    pub fn check_value(&self, value: &str) -> Result<(), String> {
+        match *self {
+            Flag => {
+                match value {
+                    "true" | "false" => Ok(()),
+                    _ => Err(String::from("Invalid flag value.")),
+                }
+            }
+            Number(_, min, max) => {
+                let n = match value.parse::<i32>() {
+                    Ok(v) => v,
+                    Err(e) => return Err(format!("{}", e)),
+                };
+
+                if n >= min && n <= max {
+                    Ok(())
+                } else {
+                    Err(String::from("Invalid value."))
+                }
+            }
+            List(_) => Ok(()),
+            Distance(_) => {
+                match Tolerance::parse(value) {
+                    Some(_) => Ok(()),
+                    None => Err(String::from("Invalid value.")),
+                }
+            }
+        }
+    }
+}
+
+impl OptionInfo {
+    /// Checks that a string is a valid value of this option.
+    pub fn check_value(&self, value: &str) -> Result<(), String> {
+        self.kind.check_value(value)
+    }
+}
+
+/// A maximum allowed deviation.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Tolerance {
+    /// In user units.
+    UserUnits(f64),
+    /// In percents of the largest side of the root element viewport.
+    Viewport(f64),
+}
+
+impl Tolerance {
+    /// Parses a `Distance` option value, like `0.5` or `0.1%`.
+    pub fn parse(value: &str) -> Option<Tolerance> {
+        let (value, is_percent) = match value.rfind('%') {
+            Some(idx) if idx + 1 == value.len() => (&value[..idx], true),
+            _ => (value, false),
+        };
+
+        let n = match value.parse::<f64>() {
+            Ok(n) if n.is_finite() && n >= 0.0 => n,
+            _ => return None,
+        };
+
+        if is_percent {
+            Some(Tolerance::Viewport(n))
+        } else {
+            Some(Tolerance::UserUnits(n))
+        }
+    }
+}
+
+/// Splits a `List` option value into items.
+///
+/// Empty items are ignored.
+pub fn split_list(value: &str) -> Vec<String> {
+    value.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).map(String::from).collect()
+}
+
+// The type of the `Options` field of the specified kind.
+macro_rules! option_type {
+    (Flag) => (bool);
+    (Number) => (i32);
+    (List) => (Vec<String>);
+    (Distance) => (Tolerance);
+}
+
+// The initial value of the `Options` field of the specified kind.
+// Flags are taken from the getter, other options use the registry defaults.
+macro_rules! option_value {
+    (Flag, $key:expr, $f:ident) => ($f($key));
+    (Number, $key:expr, $f:ident) => ($key.default_number());
+    (List, $key:expr, $f:ident) => (split_list($key.info().default));
+    (Distance, $key:expr, $f:ident) => (Tolerance::parse($key.info().default).unwrap());
+}
+
+macro_rules! option_flag {
+    (Flag, $value:expr) => (Some($value));
+    ($kind:ident, $value:expr) => (None);
+}
+
+// Options with a field are stored in the `Options` struct.
+// The field type depends on the option kind.
+// Other options are parsing, writing or CLI options.
+macro_rules! options {
+    ($(
+        $group:ident {
+            $(
+                $key:ident: $name:expr, $kind:ident $(($($kind_arg:expr),*))*,
+                $default:expr, $descr:expr
+                $(=> $field:ident)* $(, requires $dep:ident)*;
+            )*
+        }
+    )*) => (
+        #[derive(Clone, Copy, PartialEq, Debug)]
+        pub enum Key {
+            $($($key,)*)*
+        }
+
+        pub static KEYS: &'static KeysData<'static> = &KeysData(&[
+            $($($name,)*)*
+        ]);
+
+        pub static OPTIONS: &'static [OptionInfo] = &[
+            $($(
+                OptionInfo {
+                    key: Key::$key,
+                    name: $name,
+                    group: Group::$group,
+                    kind: $kind $(($($kind_arg),*))*,
+                    default: $default,
+                    depends_on: &[$(Key::$dep),*],
+                    description: $descr,
+                },
+            )*)*
+        ];
+
+        /// Documentation can be found in: docs/svgcleaner.rst
+        pub struct Options {
+            $($($(
+                #[doc = $descr]
+                pub $field: option_type!($kind),
+            )*)*)*
+        }
+
+        impl Options {
+            /// Creates cleaning options using a flag value getter.
+            ///
+            /// Other options are set to the default values.
+            pub fn from_flags<F>(f: F) -> Options
+                where F: Fn(Key) -> bool
+            {
+                Options {
+                    $($($( $field: option_value!($kind, Key::$key, f), )*)*)*
+                }
+            }
+
+            /// Returns a flag value by the key.
+            ///
+            /// Returns `None` if the key is not a cleaning option.
+            pub fn flag(&self, key: Key) -> Option<bool> {
+                match key {
+                    $($($( Key::$key => option_flag!($kind, self.$field), )*)*)*
+                    _ => None,
+                }
+            }
+        }
+    )
+}
+
+options! {
+    Elements {
+        RemoveComments: "remove-comments", Flag, "true",
+            "Remove XML comments";
+        RemoveDeclarations: "remove-declarations", Flag, "true",
+            "Remove XML declarations";
+        RemoveNonsvgElements: "remove-nonsvg-elements", Flag, "true",
+            "Remove non-SVG elements";
+        RemoveUnusedDefs: "remove-unused-defs", Flag, "true",
+            "Remove unused referenced elements" => remove_unused_defs;
+        ConvertShapes: "convert-shapes", Flag, "true",
+            "Convert basic shapes into paths" => convert_shapes;
+        RemoveTitle: "remove-title", Flag, "true",
+            "Remove 'title' element" => remove_title;
+        RemoveDesc: "remove-desc", Flag, "true",
+            "Remove 'desc' element" => remove_desc;
+        RemoveMetadata: "remove-metadata", Flag, "true",
+            "Remove 'metadata' element" => remove_metadata;
+        RemoveDuplLinearGradients: "remove-dupl-lineargradient", Flag, "true",
+            "Remove duplicated 'linearGradient' elements" => remove_dupl_linear_gradients;
+        RemoveDuplRadialGradients: "remove-dupl-radialgradient", Flag, "true",
+            "Remove duplicated 'radialGradient' elements" => remove_dupl_radial_gradients;
+        RemoveDuplFeGaussianBlur: "remove-dupl-fegaussianblur", Flag, "true",
+            "Remove duplicated 'feGaussianBlur' elements" => remove_dupl_fe_gaussian_blur;
+        UngroupGroups: "ungroup-groups", Flag, "true",
+            "Ungroup groups" => ungroup_groups;
+        UngroupDefs: "ungroup-defs", Flag, "true",
+            "Ungroup 'defs' element" => ungroup_defs;
+        GroupByStyle: "group-by-style", Flag, "true",
+            "Group elements by equal styles" => group_by_style;
+        MergeGradients: "merge-gradients", Flag, "true",
+            "Merge gradients" => merge_gradients;
+        RegroupGradientStops: "regroup-gradient-stops", Flag, "true",
+            "Regroup gradient 'stop' elements" => regroup_gradient_stops;
+        RemoveInvalidStops: "remove-invalid-stops", Flag, "true",
+            "Remove invalid 'stop' elements" => remove_invalid_stops;
+        RemoveInvisibleElements: "remove-invisible-elements", Flag, "true",
+            "Remove invisible elements" => remove_invisible_elements;
+        ResolveUse: "resolve-use", Flag, "true",
+            "Resolve 'use' elements" => resolve_use;
+    }
+    Attributes {
+        RemoveVersion: "remove-version", Flag, "true",
+            "Remove 'version' and 'baseProfile' attributes" => remove_version;
+        RemoveNonsvgAttributes: "remove-nonsvg-attributes", Flag, "true",
+            "Remove non-SVG attributes";
+        RemoveUnreferencedIds: "remove-unreferenced-ids", Flag, "true",
+            "Remove unreferenced 'id' attributes" => remove_unreferenced_ids;
+        TrimIds: "trim-ids", Flag, "true",
+            "Trim 'id' attributes" => trim_ids;
+        RemoveTextAttributes: "remove-text-attributes", Flag, "true",
+            "Remove text-related attributes if there is no text" => remove_text_attributes;
+        RemoveUnusedCoordinates: "remove-unused-coordinates", Flag, "true",
+            "Remove unused coordinate attributes" => remove_unused_coordinates;
+        RemoveDefaultAttributes: "remove-default-attributes", Flag, "true",
+            "Remove attributes with default values" => remove_default_attributes;
+        RemoveXmlnsXlinkAttribute: "remove-xmlns-xlink-attribute", Flag, "true",
+            "Remove an unused 'xmlns:xlink' attribute" => remove_xmlns_xlink_attribute;
+        RemoveNeedlessAttributes: "remove-needless-attributes", Flag, "true",
+            "Remove attributes that doesn't belong to this element" => remove_needless_attributes;
+        RemoveGradientAttributes: "remove-gradient-attributes", Flag, "false",
+            "Remove inheritable gradient attributes" => remove_gradient_attributes;
+        JoinStyleAttributes: "join-style-attributes", Flag, "true",
+            "Join presentational attributes" => join_style_attributes;
+        ApplyTransformToGradients: "apply-transform-to-gradients", Flag, "true",
+            "Apply transformations to gradients" => apply_transform_to_gradients;
+        ApplyTransformToShapes: "apply-transform-to-shapes", Flag, "true",
+            "Apply transformations to shapes" => apply_transform_to_shapes;
+        ApplyTransformToPaths: "apply-transform-to-paths", Flag, "false",
+            "Apply transformations to paths" => apply_transform_to_paths;
+        RemoveUnresolvedClasses: "remove-unresolved-classes", Flag, "true",
+            "Remove unresolved classes from 'class' attributes";
+    }
+    Paths {
+        PathsToRelative: "paths-to-relative", Flag, "true",
+            "Convert path segments into relative ones" => paths_to_relative;
+        RemoveUnusedSegments: "remove-unused-segments", Flag, "true",
+            "Remove unused path segments" => remove_unused_segments,
+            requires PathsToRelative;
+        ConvertSegments: "convert-segments", Flag, "true",
+            "Convert path segments into shorter ones" => convert_segments,
+            requires PathsToRelative;
+        TrimPaths: "trim-paths", Flag, "true",
+            "Use compact notation for paths";
+        JoinArcToFlags: "join-arcto-flags", Flag, "false",
+            "Join ArcTo flags",
+            requires TrimPaths;
+        RemoveDuplCmdInPaths: "remove-dupl-cmd-in-paths", Flag, "true",
+            "Remove subsequent segments command from paths";
+        UseImplicitCommands: "use-implicit-cmds", Flag, "true",
+            "Use implicit LineTo commands";
+        SimplifyPaths: "simplify-paths", Flag, "false",
+            "Simplify paths within a tolerance. Lossy" => simplify_paths,
+            requires ConvertSegments;
+        SimplifyPathsTolerance: "simplify-paths-tolerance", Distance("VALUE"), "0.1",
+            "Set maximum deviation for '--simplify-paths'" => simplify_paths_tolerance;
+    }
+    Output {
+        TrimColors: "trim-colors", Flag, "true",
+            "Use #RGB notation";
+        SimplifyTransforms: "simplify-transforms", Flag, "true",
+            "Simplify transform matrices";
+        PathsCoordinatesPrecision: "paths-coordinates-precision", Number("VALUE", 1, 12), "8",
+            "Set path's coordinates numeric precision";
+        AutoPrecision: "auto-precision", Flag, "false",
+            "Choose coordinates precision using the viewport size" => auto_precision;
+        AutoPrecisionError: "auto-precision-error", Distance("VALUE"), "0.1",
+            "Set maximum rendering error in pixels for '--auto-precision'"
+            => auto_precision_error;
+        Indent: "indent", Number("INDENT", -1, 4), "-1",
+            "Sets XML nodes indent";
+    }
+    Other {
+        Multipass: "multipass", Flag, "false",
+            "Clean a file multiple times" => multipass;
+        AllowAnimation: "allow-animation", Flag, "false",
+            "Keep SMIL animations and the nodes they affect" => allow_animation;
+        AllowScripts: "allow-scripts", Flag, "false",
+            "Keep scripts, ids and the nodes with event attributes" => allow_scripts;
+        AllowExternalLinks: "allow-external-links", Flag, "false",
+            "Keep elements with external 'xlink:href' links as is" => allow_external_links;
+        ResolveSwitch: "resolve-switch", Flag, "false",
+            "Keep only rendered children of 'switch' elements" => resolve_switch;
+        SwitchLanguages: "switch-languages", List("LANGS"), "en",
+            "Set user languages for '--resolve-switch'" => switch_languages;
+        SwitchFeatures: "switch-features", List("FEATURES"), "",
+            "Set supported features and extensions for '--resolve-switch'"
+            => switch_features;
+        CopyOnError: "copy-on-error", Flag, "false",
+            "Copy an original file to the destination on error";
+        Quiet: "quiet", Flag, "false",
+            "Show only warnings and errors";
+        Verbose: "verbose", Flag, "false",
+            "Show statistics for each cleaning pass";
+        CheckOnly: "check-only", Flag, "false",
+            "Check a file for problems without cleaning it";
+        Check: "check", Flag, "false",
+            "Check that files are already cleaned without writing them";
+        CheckThreshold: "check-threshold", Number("PERCENT", 0, 100), "0",
+            "Ignore files that would shrink by less than the specified percent";
+        Compress: "compress", Flag, "false",
+            "Compress the output file using gzip";
+        CompressionLevel: "compression-level", Number("LEVEL", 1, 9), "6",
+            "Set the gzip compression level";
+    }
+}
+
+pub struct KeysData<'a>(&'a [&'a str]);
+
+impl<'a> Index<Key> for KeysData<'a> {
+    type Output = &'a str;
+
+    fn index(&self, _index: Key) -> &&'a str {
+        &self.0[_index as usize]
+    }
+}
+
+impl Key {
+    /// Returns an option description.
+    pub fn info(&self) -> &'static OptionInfo {
+        &OPTIONS[*self as usize]
+    }
+
+    /// Returns a name of the CLI argument.
+    pub fn name(&self) -> &'static str {
+        self.info().name
+    }
+
+    /// Returns a default value of the `Number` option.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the option is not a `Number`.
+    pub fn default_number(&self) -> i32 {
+        match self.info().kind {
+            Number(..) => self.info().default.parse().unwrap(),
+            Flag | List(..) | Distance(..) => panic!("'{}' is not a number", self.name()),
+        }
+    }
 }
 
 impl Default for Options {
+    /// Creates cleaning options with all flags disabled.
     fn default() -> Options {
-        Options {
-            remove_unused_defs: false,
-            convert_shapes: false,
-            remove_title: false,
-            remove_desc: false,
-            remove_metadata: false,
-            remove_dupl_linear_gradients: false,
-            remove_dupl_radial_gradients: false,
-            remove_dupl_fe_gaussian_blur: false,
-            ungroup_groups: false,
-            ungroup_defs: false,
-            group_by_style: false,
-            merge_gradients: false,
-            regroup_gradient_stops: false,
-            remove_invalid_stops: false,
-            remove_invisible_elements: false,
-            resolve_use: false,
-
-            remove_version: false,
-            remove_unreferenced_ids: false,
-            trim_ids: false,
-            remove_text_attributes: false,
-            remove_unused_coordinates: false,
-            remove_default_attributes: false,
-            remove_xmlns_xlink_attribute: false,
-            remove_needless_attributes: false,
-            remove_gradient_attributes: false,
-            join_style_attributes: false,
-            apply_transform_to_gradients: false,
-            apply_transform_to_shapes: false,
-
-            paths_to_relative: false,
-            remove_unused_segments: false,
-            convert_segments: false,
+        Options::from_flags(|_| false)
+    }
+}
+
+/// Cleaning presets.
+///
+/// Individual options can be changed after a preset was applied.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Preset {
+    /// Only options that doesn't change the document structure, ids and paths geometry.
+    Safe,
+    /// Same as the `svgcleaner` CLI defaults.
+    Default,
+    /// `Default` plus `remove_gradient_attributes`, `join_arc_to_flags` and `multipass`.
+    Aggressive,
+}
+
+// Options that are enabled by default, but disabled by the `Safe` preset.
+static SAFE_DISABLED: &'static [Key] = &[
+    // keep the tree structure
+    Key::ConvertShapes,
+    Key::UngroupGroups,
+    Key::UngroupDefs,
+    Key::GroupByStyle,
+    Key::MergeGradients,
+    Key::RegroupGradientStops,
+    Key::RemoveInvisibleElements,
+    Key::ResolveUse,
+
+    // keep elements that can be used by an external code
+    Key::RemoveTitle,
+    Key::RemoveDesc,
+    Key::RemoveUnreferencedIds,
+    Key::TrimIds,
+
+    // keep coordinates as is
+    Key::ApplyTransformToGradients,
+    Key::ApplyTransformToShapes,
+    Key::RemoveUnusedSegments,
+    Key::ConvertSegments,
+];
+
+// Options that are disabled by default, but enabled by the `Aggressive` preset.
+static AGGRESSIVE_ENABLED: &'static [Key] = &[
+    Key::RemoveGradientAttributes,
+    Key::JoinArcToFlags,
+    Key::Multipass,
+];
+
+impl Preset {
+    /// Parses a preset name: `safe`, `default` or `aggressive`.
+    pub fn from_name(name: &str) -> Option<Preset> {
+        match name {
+            "safe" => Some(Preset::Safe),
+            "default" => Some(Preset::Default),
+            "aggressive" => Some(Preset::Aggressive),
+            _ => None,
+        }
+    }
+
+    /// Returns a preset name.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Preset::Safe => "safe",
+            Preset::Default => "default",
+            Preset::Aggressive => "aggressive",
+        }
+    }
+
+    /// Returns a flag value for this preset.
+    ///
+    /// Always returns `false` for non-flag options.
+    pub fn flag(&self, key: Key) -> bool {
+        match *self {
+            Preset::Safe if SAFE_DISABLED.contains(&key) => false,
+            Preset::Aggressive if AGGRESSIVE_ENABLED.contains(&key) => true,
+            _ => key.info().default == "true",
         }
     }
+
+    /// Returns parsing options for this preset.
+    pub fn parse_options(&self) -> ParseOptions {
+        let mut opt = ParseOptions::default();
+
+        opt.parse_comments              = !self.flag(Key::RemoveComments);
+        opt.parse_declarations          = !self.flag(Key::RemoveDeclarations);
+        opt.parse_unknown_elements      = !self.flag(Key::RemoveNonsvgElements);
+        opt.parse_unknown_attributes    = !self.flag(Key::RemoveNonsvgAttributes);
+        opt.skip_unresolved_classes     =  self.flag(Key::RemoveUnresolvedClasses);
+        opt.parse_px_unit               = false;
+
+        opt
+    }
+
+    /// Returns writing options for this preset.
+    pub fn write_options(&self) -> WriteOptions {
+        let mut opt = WriteOptions::default();
+
+        opt.paths.use_compact_notation          = self.flag(Key::TrimPaths);
+        opt.paths.remove_duplicated_commands    = self.flag(Key::RemoveDuplCmdInPaths);
+        opt.paths.join_arc_to_flags             = self.flag(Key::JoinArcToFlags);
+        opt.paths.use_implicit_lineto_commands  = self.flag(Key::UseImplicitCommands);
+        opt.paths.coordinates_precision
+            = Key::PathsCoordinatesPrecision.default_number() as u8;
+
+        opt.simplify_transform_matrices = self.flag(Key::SimplifyTransforms);
+
+        opt.remove_leading_zero = true;
+
+        opt.trim_hex_colors = self.flag(Key::TrimColors);
+        opt.indent = Key::Indent.default_number() as i8;
+
+        opt
+    }
+
+    /// Returns cleaning options for this preset.
+    pub fn cleaning_options(&self) -> Options {
+        Options::from_preset(*self)
+    }
+}
+
+impl Options {
+    /// Creates cleaning options from a preset.
+    pub fn from_preset(preset: Preset) -> Options {
+        Options::from_flags(|key| preset.flag(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_1() {
+        // keys and options must be in the same order
+        for (i, info) in OPTIONS.iter().enumerate() {
+            assert_eq!(info.key as usize, i);
+            assert_eq!(KEYS[info.key], info.name);
+        }
+    }
+
+    #[test]
+    fn registry_2() {
+        // dependencies must be flags
+        for info in OPTIONS {
+            for dep in info.depends_on {
+                assert_eq!(dep.info().kind, Flag);
+            }
+        }
+    }
+
+    #[test]
+    fn check_value_1() {
+        assert_eq!(Key::RemoveTitle.info().check_value("true"), Ok(()));
+        assert!(Key::RemoveTitle.info().check_value("1").is_err());
+        assert_eq!(Key::Indent.info().check_value("-1"), Ok(()));
+        assert!(Key::Indent.info().check_value("5").is_err());
+        assert!(Key::Indent.info().check_value("true").is_err());
+    }
+
+    #[test]
+    fn split_list_1() {
+        assert_eq!(split_list("en, de-DE,,"), vec!["en", "de-DE"]);
+        assert_eq!(split_list(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn tolerance_1() {
+        assert_eq!(Tolerance::parse("0.5"), Some(Tolerance::UserUnits(0.5)));
+        assert_eq!(Tolerance::parse("1%"), Some(Tolerance::Viewport(1.0)));
+        assert_eq!(Tolerance::parse("-1"), None);
+        assert_eq!(Tolerance::parse("%"), None);
+        assert!(Key::SimplifyPathsTolerance.info().check_value("0.1%").is_ok());
+    }
+
+    #[test]
+    fn presets_1() {
+        let opt = Options::from_preset(Preset::Default);
+        assert_eq!(opt.trim_ids, true);
+        assert_eq!(opt.remove_gradient_attributes, false);
+        assert_eq!(opt.multipass, false);
+        assert_eq!(Preset::Default.write_options().paths.join_arc_to_flags, false);
+    }
+
+    #[test]
+    fn presets_2() {
+        let opt = Options::from_preset(Preset::Safe);
+        assert_eq!(opt.trim_ids, false);
+        assert_eq!(opt.ungroup_groups, false);
+        assert_eq!(opt.remove_version, true);
+    }
+
+    #[test]
+    fn presets_3() {
+        let opt = Options::from_preset(Preset::Aggressive);
+        assert_eq!(opt.remove_gradient_attributes, true);
+        // lossy-prone passes are opt-in
+        assert_eq!(opt.apply_transform_to_paths, false);
+        assert_eq!(opt.multipass, true);
+        assert_eq!(Preset::Aggressive.write_options().paths.join_arc_to_flags, true);
+    }
 }
@@ -0,0 +1,489 @@
+/****************************************************************************
+**
+** svgcleaner could help you to clean up your SVG files
+** from unnecessary data.
+** Copyright (C) 2012-2017 Evgeniy Reizner
+**
+** This program is free software; you can redistribute it and/or modify
+** it under the terms of the GNU General Public License as published by
+** the Free Software Foundation; either version 2 of the License, or
+** (at your option) any later version.
+**
+** This program is distributed in the hope that it will be useful,
+** but WITHOUT ANY WARRANTY; without even the implied warranty of
+** MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+** GNU General Public License for more details.
+**
+** You should have received a copy of the GNU General Public License along
+** with this program; if not, write to the Free Software Foundation, Inc.,
+** 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+**
+****************************************************************************/
+
+
+//! The cleaning passes pipeline.
+
+use std::time::{Duration, Instant};
+
+use svgdom::{Document, Node, WriteOptions, WriteBuffer, ElementId};
+
+use svgdom_utils;
+
+use error::Error;
+use options::{Options, Key};
+use task::*;
+
+/// A cleaning pass.
+pub trait CleaningPass {
+    /// Returns a unique pass name.
+    ///
+    /// Built-in passes controlled by an option have the same name as a CLI argument
+    /// of this option. Like `remove-title`.
+    fn name(&self) -> &str;
+
+    /// Checks that pass should be executed with the specified options.
+    fn is_enabled(&self, _options: &Options) -> bool {
+        true
+    }
+
+    /// Executes the pass.
+    fn run(&self, doc: &Document, options: &Options, write_opt: &WriteOptions)
+           -> Result<(), Error>;
+}
+
+struct BuiltinPass {
+    name: &'static str,
+    is_enabled: fn(&Options) -> bool,
+    run: fn(&Document, &Options, &WriteOptions) -> Result<(), Error>,
+}
+
+impl CleaningPass for BuiltinPass {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn is_enabled(&self, options: &Options) -> bool {
+        (self.is_enabled)(options)
+    }
+
+    fn run(&self, doc: &Document, options: &Options, write_opt: &WriteOptions)
+           -> Result<(), Error> {
+        (self.run)(doc, options, write_opt)
+    }
+}
+
+macro_rules! builtin {
+    ($name:expr, $is_enabled:expr, $run:expr) => (
+        Box::new(BuiltinPass {
+            name: $name,
+            is_enabled: $is_enabled,
+            run: $run,
+        })
+    );
+    ($key:expr, $run:expr) => (
+        builtin!($key.name(), |o| o.flag($key).unwrap(), |doc, _, _| { $run(doc); Ok(()) })
+    );
+}
+
+/// Statistics of a single pass execution.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PassReport {
+    /// A pass name.
+    pub name: String,
+    /// A multipass iteration number, starting from 1.
+    pub iteration: u32,
+    /// Amount of the removed SVG elements.
+    pub elements_removed: usize,
+    /// Amount of the removed attributes of the remaining SVG elements.
+    pub attributes_removed: usize,
+    /// Amount of the SVG elements that have a new parent.
+    pub nodes_moved: usize,
+    /// A pass execution time.
+    pub time: Duration,
+    /// A serialized document size change in bytes.
+    ///
+    /// A negative value means that the document became smaller.
+    /// `None` when size measuring is disabled.
+    pub size_change: Option<i64>,
+}
+
+// An element state before the pass.
+struct NodeState {
+    node: Node,
+    parent: Option<Node>,
+    attrs_count: usize,
+}
+
+fn take_snapshot(doc: &Document) -> Vec<NodeState> {
+    doc.descendants().svg().map(|n| {
+        NodeState {
+            parent: n.parent(),
+            attrs_count: n.attributes().iter().count(),
+            node: n,
+        }
+    }).collect()
+}
+
+fn is_attached(node: &Node, svg: &Node) -> bool {
+    node == svg || node.parents().any(|p| p == *svg)
+}
+
+fn serialized_size(doc: &Document, opt: &WriteOptions, buf: &mut Vec<u8>) -> i64 {
+    buf.clear();
+    doc.write_buf_opt(opt, buf);
+    buf.len() as i64
+}
+
+/// An ordered list of the cleaning passes.
+///
+/// `Pipeline::default()` contains all built-in passes in the required order.
+pub struct Pipeline {
+    passes: Vec<Box<dyn CleaningPass>>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Pipeline {
+        // NOTE: Order is important.
+        //       Methods should not depend on each other, but for performance reasons
+        //       they should be executed in this order.
+
+        let passes: Vec<Box<dyn CleaningPass>> = vec![
+            builtin!("preclean-checks", |_| true, |doc, o, _| preclean_checks(doc, o)),
+
+            // Remove elements that will not be rendered before any other processing.
+            builtin!(Key::ResolveSwitch.name(), |o| o.resolve_switch,
+                     |doc, o, _| { resolve_switch(doc, o); Ok(()) }),
+
+            // Prepare our document.
+            // This methods is not optional.
+            builtin!("resolve-attributes", |_| true, |doc, _, _| {
+                utils::resolve_gradient_attributes(doc)?;
+                svgdom_utils::resolve_inherit(doc)?;
+                Ok(())
+            }),
+            builtin!("fix-invalid-attributes", |_| true, |doc, _, _| {
+                fix_invalid_attributes(doc);
+                Ok(())
+            }),
+            builtin!("group-defs", |_| true, |doc, _, _| { group_defs(doc); Ok(()) }),
+
+            // Manipulate with tree structure.
+            // Do not remove any attributes before this methods
+            // since they uses them.
+
+            builtin!(Key::RemoveTitle, |doc| remove_element(doc, ElementId::Title)),
+            builtin!(Key::RemoveDesc, |doc| remove_element(doc, ElementId::Desc)),
+            builtin!(Key::RemoveMetadata, |doc| remove_element(doc, ElementId::Metadata)),
+            builtin!(Key::RemoveUnusedDefs, remove_unused_defs),
+            builtin!(Key::RemoveInvalidStops, remove_invalid_stops),
+
+            // Apply transform to gradients before processing to simplify duplicates
+            // detecting and merging.
+            builtin!(Key::ApplyTransformToGradients,
+                     apply_transforms::apply_transform_to_gradients),
+
+            builtin!(Key::RemoveDuplLinearGradients, remove_dupl_linear_gradients),
+            builtin!(Key::RemoveDuplRadialGradients, remove_dupl_radial_gradients),
+            builtin!(Key::RemoveDuplFeGaussianBlur, remove_dupl_fe_gaussian_blur),
+            builtin!(Key::MergeGradients, merge_gradients),
+
+            // Do it again, because something may changed after gradient processing.
+            builtin!("reapply-transform-to-gradients", |o| o.apply_transform_to_gradients,
+                     |doc, _, _| { apply_transforms::apply_transform_to_gradients(doc); Ok(()) }),
+
+            // Apply before 'convert_shapes_to_paths'.
+            builtin!(Key::ApplyTransformToShapes, apply_transforms::apply_transform_to_shapes),
+
+            builtin!(Key::ConvertShapes, convert_shapes_to_paths),
+
+            // Apply after 'convert_shapes_to_paths', so converted shapes will be processed too,
+            // and before paths processing, which converts segments into relative ones.
+            builtin!(Key::ApplyTransformToPaths, apply_transforms::apply_transform_to_paths),
+
+            // NOTE: run before `remove_invisible_elements`, because this method can remove all
+            //       segments from the path which makes it invisible.
+            // We only process path's segments if 'PathsToRelative' is enabled.
+            builtin!(Key::PathsToRelative.name(), |o| o.paths_to_relative,
+                     |doc, o, w| { paths::process_paths(doc, o, w); Ok(()) }),
+
+            // Round coordinates after all transforms were applied and paths were processed,
+            // because path processing relies on the original geometry.
+            builtin!(Key::AutoPrecision.name(), |o| o.auto_precision,
+                     |doc, o, w| { round_coordinates(doc, o, w); Ok(()) }),
+
+            builtin!(Key::RemoveInvisibleElements, remove_invisible_elements),
+            builtin!(Key::RegroupGradientStops, regroup_gradient_stops),
+            builtin!(Key::UngroupGroups, ungroup_groups),
+            builtin!(Key::ResolveUse, resolve_use),
+
+            // now we can remove any unneeded attributes
+
+            builtin!(Key::RemoveDefaultAttributes, remove_default_attributes),
+            builtin!(Key::RemoveTextAttributes, remove_text_attributes),
+            builtin!(Key::RemoveNeedlessAttributes, remove_needless_attributes),
+            builtin!(Key::RemoveGradientAttributes, remove_gradient_attributes),
+            builtin!(Key::RemoveUnusedCoordinates, remove_unused_coordinates),
+
+            // Run only after attributes processed, because
+            // there is no point in grouping default/unneeded attributes.
+            builtin!(Key::GroupByStyle, group_by_style),
+
+            // final fixes
+            // list of things that can't break anything
+
+            builtin!(Key::RemoveUnreferencedIds, remove_unreferenced_ids),
+            builtin!(Key::TrimIds, trim_ids),
+            builtin!(Key::RemoveVersion, remove_version),
+            builtin!(Key::UngroupDefs, ungroup_defs),
+
+            builtin!("remove-empty-defs", |_| true, |doc, _, _| { remove_empty_defs(doc); Ok(()) }),
+            builtin!("fix-xmlns-attribute", |_| true, |doc, o, _| {
+                fix_xmlns_attribute(doc, o.remove_xmlns_xlink_attribute);
+                Ok(())
+            }),
+
+            // NOTE: must be run at last, since it breaks the linking.
+            builtin!(Key::JoinStyleAttributes.name(), |o| o.join_style_attributes,
+                     |doc, _, opt| { join_style_attributes(doc, opt); Ok(()) }),
+        ];
+
+        Pipeline {
+            passes: passes,
+        }
+    }
+}
+
+impl Pipeline {
+    /// Creates an empty pipeline.
+    pub fn empty() -> Pipeline {
+        Pipeline {
+            passes: Vec::new(),
+        }
+    }
+
+    /// Returns passes names in the execution order.
+    pub fn names(&self) -> Vec<&str> {
+        self.passes.iter().map(|p| p.name()).collect()
+    }
+
+    /// Appends a pass to the end of the pipeline.
+    pub fn push(&mut self, pass: Box<dyn CleaningPass>) {
+        self.passes.push(pass);
+    }
+
+    /// Inserts a pass before the pass with the specified name.
+    ///
+    /// Returns `false` if there is no such pass.
+    pub fn insert_before(&mut self, name: &str, pass: Box<dyn CleaningPass>) -> bool {
+        match self.position(name) {
+            Some(idx) => {
+                self.passes.insert(idx, pass);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Inserts a pass after the pass with the specified name.
+    ///
+    /// Returns `false` if there is no such pass.
+    pub fn insert_after(&mut self, name: &str, pass: Box<dyn CleaningPass>) -> bool {
+        match self.position(name) {
+            Some(idx) => {
+                self.passes.insert(idx + 1, pass);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the pass with the specified name.
+    ///
+    /// Returns `false` if there is no such pass.
+    pub fn remove(&mut self, name: &str) -> bool {
+        match self.position(name) {
+            Some(idx) => {
+                self.passes.remove(idx);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.passes.iter().position(|p| p.name() == name)
+    }
+
+    /// Executes all enabled passes and collects statistics for each of them.
+    ///
+    /// `measure_size` enables the `PassReport::size_change` calculation,
+    /// which requires the document serialization after each pass, so it's very slow.
+    pub fn run_with_report(&self, doc: &Document, options: &Options, write_opt: &WriteOptions,
+                           measure_size: bool, iteration: u32)
+                           -> Result<Vec<PassReport>, Error> {
+        let mut reports = Vec::new();
+        let mut buf = Vec::new();
+
+        let mut prev_size = if measure_size {
+            serialized_size(doc, write_opt, &mut buf)
+        } else {
+            0
+        };
+
+        for pass in &self.passes {
+            if !pass.is_enabled(options) {
+                continue;
+            }
+
+            let snapshot = take_snapshot(doc);
+
+            let now = Instant::now();
+            pass.run(doc, options, write_opt)?;
+            let time = now.elapsed();
+
+            let mut report = PassReport {
+                name: pass.name().to_owned(),
+                iteration: iteration,
+                elements_removed: 0,
+                attributes_removed: 0,
+                nodes_moved: 0,
+                time: time,
+                size_change: None,
+            };
+
+            // doc must contain 'svg' node, so we can safely unwrap
+            let svg = doc.svg_element().unwrap();
+            for state in snapshot {
+                if !is_attached(&state.node, &svg) {
+                    report.elements_removed += 1;
+                    continue;
+                }
+
+                if state.node.parent() != state.parent {
+                    report.nodes_moved += 1;
+                }
+
+                let attrs_count = state.node.attributes().iter().count();
+                if attrs_count < state.attrs_count {
+                    report.attributes_removed += state.attrs_count - attrs_count;
+                }
+            }
+
+            if measure_size {
+                let size = serialized_size(doc, write_opt, &mut buf);
+                report.size_change = Some(size - prev_size);
+                prev_size = size;
+            }
+
+            reports.push(report);
+        }
+
+        Ok(reports)
+    }
+
+    /// Executes all enabled passes.
+    pub fn run(&self, doc: &Document, options: &Options, write_opt: &WriteOptions)
+               -> Result<(), Error> {
+        for pass in &self.passes {
+            if pass.is_enabled(options) {
+                pass.run(doc, options, write_opt)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use svgdom::{Document, WriteOptions};
+    use options::{Options, Key};
+    use error::Error;
+
+    struct LogPass {
+        name: &'static str,
+        log: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    impl CleaningPass for LogPass {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn run(&self, _: &Document, _: &Options, _: &WriteOptions) -> Result<(), Error> {
+            self.log.borrow_mut().push(self.name);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn builtin_names() {
+        let p = Pipeline::default();
+        let names = p.names();
+
+        assert_eq!(names[0], "preclean-checks");
+        assert_eq!(*names.last().unwrap(), Key::JoinStyleAttributes.name());
+
+        // names must be unique
+        for name in &names {
+            assert_eq!(names.iter().filter(|n| *n == name).count(), 1);
+        }
+    }
+
+    #[test]
+    fn insert_1() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        let mut p = Pipeline::empty();
+        p.push(Box::new(LogPass { name: "b", log: log.clone() }));
+        assert!(p.insert_before("b", Box::new(LogPass { name: "a", log: log.clone() })));
+        assert!(p.insert_after("b", Box::new(LogPass { name: "c", log: log.clone() })));
+        assert!(!p.insert_after("d", Box::new(LogPass { name: "e", log: log.clone() })));
+
+        let doc = Document::from_data(b"<svg/>").unwrap();
+        p.run(&doc, &Options::default(), &WriteOptions::default()).unwrap();
+
+        assert_eq!(*log.borrow(), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn report_1() {
+        let doc = Document::from_data(b"<svg><title/><desc/><g><rect/></g></svg>").unwrap();
+
+        let mut opt = Options::default();
+        opt.remove_title = true;
+        opt.ungroup_groups = true;
+
+        let reports = Pipeline::default().run_with_report(&doc, &opt, &WriteOptions::default(),
+                                                          true, 1).unwrap();
+
+        let title = reports.iter().find(|r| r.name == Key::RemoveTitle.name()).unwrap();
+        assert_eq!(title.elements_removed, 1);
+        assert!(title.size_change.unwrap() < 0);
+
+        let ungroup = reports.iter().find(|r| r.name == Key::UngroupGroups.name()).unwrap();
+        assert_eq!(ungroup.elements_removed, 1);
+        assert_eq!(ungroup.nodes_moved, 1);
+
+        // disabled passes are not reported
+        assert!(reports.iter().find(|r| r.name == Key::RemoveDesc.name()).is_none());
+    }
+
+    #[test]
+    fn insert_2() {
+        let mut p = Pipeline::default();
+        let len = p.names().len();
+        assert!(p.insert_after(Key::TrimIds.name(), Box::new(LogPass {
+            name: "custom",
+            log: Rc::new(RefCell::new(Vec::new())),
+        })));
+
+        let names = p.names();
+        assert_eq!(names.len(), len + 1);
+        let idx = names.iter().position(|n| *n == "custom").unwrap();
+        assert_eq!(names[idx - 1], Key::TrimIds.name());
+    }
+}
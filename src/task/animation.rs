@@ -0,0 +1,170 @@
+/****************************************************************************
+**
+** svgcleaner could help you to clean up your SVG files
+** from unnecessary data.
+** Copyright (C) 2012-2017 Evgeniy Reizner
+**
+** This program is free software; you can redistribute it and/or modify
+** it under the terms of the GNU General Public License as published by
+** the Free Software Foundation; either version 2 of the License, or
+** (at your option) any later version.
+**
+** This program is distributed in the hope that it will be useful,
+** but WITHOUT ANY WARRANTY; without even the implied warranty of
+** MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+** GNU General Public License for more details.
+**
+** You should have received a copy of the GNU General Public License along
+** with this program; if not, write to the Free Software Foundation, Inc.,
+** 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+**
+****************************************************************************/
+
+
+//! Helpers for documents with SMIL animations.
+//!
+//! Animation elements are rejected by `preclean_checks` unless `Options::allow_animation`
+//! is set, so for other documents this methods are no-op.
+
+use std::collections::HashSet;
+
+use super::short::{EId, AId};
+
+use svgdom::{Document, Node, AttributeValue};
+
+/// Checks that node is an animation element.
+pub fn is_animation(node: &Node) -> bool {
+    match node.tag_id() {
+          Some(EId::Animate)
+        | Some(EId::Set)
+        | Some(EId::AnimateMotion)
+        | Some(EId::AnimateColor)
+        | Some(EId::AnimateTransform) => true,
+        _ => false,
+    }
+}
+
+/// Checks that document has any animation elements.
+///
+/// Used to skip animation checks in the most of the documents.
+pub fn has_animation(doc: &Document) -> bool {
+    doc.descendants().svg().any(|n| is_animation(&n))
+}
+
+/// Returns animation elements that are targeting this node.
+fn animations(node: &Node) -> Vec<Node> {
+    let mut list: Vec<Node> = node.children().filter(|n| {
+        is_animation(n) && !n.has_attribute(AId::XlinkHref)
+    }).collect();
+
+    list.extend(node.linked_nodes().filter(|n| is_animation(n)));
+    list
+}
+
+/// Checks that node is a target of any animation element.
+pub fn is_animated(node: &Node) -> bool {
+    !animations(node).is_empty()
+}
+
+/// Checks that node attribute is changed by any animation element.
+///
+/// `animateMotion` is treated as a `transform` animation.
+pub fn is_attribute_animated(node: &Node, aid: AId) -> bool {
+    animations(node).iter().any(|anim| {
+        if anim.is_tag_name(EId::AnimateMotion) {
+            return aid == AId::Transform;
+        }
+
+        match anim.attribute_value(AId::AttributeName) {
+            Some(AttributeValue::String(ref name)) => AId::from_name(name.trim()) == Some(aid),
+            _ => false,
+        }
+    })
+}
+
+/// Same as `is_attribute_animated`, but also checks parent elements,
+/// since an inherited attribute value can be animated too.
+pub fn is_inherited_attribute_animated(node: &Node, aid: AId) -> bool {
+    is_attribute_animated(node, aid) || node.parents().any(|p| is_attribute_animated(&p, aid))
+}
+
+/// Returns ids that are referenced by the animations timing attributes.
+///
+/// Like `rect1` in `begin='rect1.click'`.
+/// This ids are not linked, so they must be preserved as is.
+pub fn collect_timing_ids(doc: &Document) -> HashSet<String> {
+    let mut ids = HashSet::new();
+
+    for node in doc.descendants().svg().filter(is_animation) {
+        for aid in &[AId::Begin, AId::End] {
+            if let Some(AttributeValue::String(ref s)) = node.attribute_value(*aid) {
+                for value in s.split(';') {
+                    if let Some(id) = parse_timing_id(value.trim()) {
+                        ids.insert(id.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    ids
+}
+
+// Parses an id part of a syncbase, event or repeat value, like 'id.end+1s'.
+fn parse_timing_id(value: &str) -> Option<&str> {
+    let idx = match value.find('.') {
+        Some(idx) => idx,
+        None => return None,
+    };
+
+    let id = &value[..idx];
+
+    // offset values, like '1.5s', and functions, like 'wallclock(...)' are not ids
+    match id.chars().next() {
+        Some(c) if c.is_alphabetic() || c == '_' => {}
+        _ => return None,
+    }
+
+    if id.contains('(') {
+        return None;
+    }
+
+    Some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::Document;
+
+    #[test]
+    fn is_animated_1() {
+        let doc = Document::from_data(b"<svg>
+            <rect id='r1'>
+                <animate attributeName='x' to='10'/>
+            </rect>
+            <rect id='r2'/>
+            <set xlink:href='#r2' attributeName='fill' to='red'/>
+            <rect id='r3'/>
+        </svg>").unwrap();
+
+        let r1 = doc.descendants().find(|n| *n.id() == "r1").unwrap();
+        let r2 = doc.descendants().find(|n| *n.id() == "r2").unwrap();
+        let r3 = doc.descendants().find(|n| *n.id() == "r3").unwrap();
+
+        assert!(is_animated(&r1));
+        assert!(is_attribute_animated(&r1, AId::X));
+        assert!(!is_attribute_animated(&r1, AId::Y));
+        assert!(is_attribute_animated(&r2, AId::Fill));
+        assert!(!is_animated(&r3));
+    }
+
+    #[test]
+    fn parse_timing_id_1() {
+        assert_eq!(parse_timing_id("r1.click"), Some("r1"));
+        assert_eq!(parse_timing_id("anim1.end+1.5s"), Some("anim1"));
+        assert_eq!(parse_timing_id("1.5s"), None);
+        assert_eq!(parse_timing_id("indefinite"), None);
+        assert_eq!(parse_timing_id("wallclock(2017-01-01T00:00:00.5Z)"), None);
+    }
+}
@@ -44,6 +44,11 @@ pub fn apply_transform_to_gradients(doc: &Document) {
             }
         }
 
+        // an external gradient can contain any attributes
+        if ::task::utils::has_external_link(&node) {
+            continue;
+        }
+
         if node.has_attribute(AId::XlinkHref) {
             // We can apply a transform to gradients which linked to other gradients
             // only when linked gradient doesn't have a transform.
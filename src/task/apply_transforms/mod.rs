@@ -22,9 +22,11 @@
 
 pub use self::gradients::apply_transform_to_gradients;
 pub use self::shapes::apply_transform_to_shapes;
+pub use self::paths::apply_transform_to_paths;
 
 mod gradients;
 mod shapes;
+mod paths;
 
 pub mod utils {
     use task::short::{EId, AId, Unit};
@@ -0,0 +1,420 @@
+/****************************************************************************
+**
+** svgcleaner could help you to clean up your SVG files
+** from unnecessary data.
+** Copyright (C) 2012-2017 Evgeniy Reizner
+**
+** This program is free software; you can redistribute it and/or modify
+** it under the terms of the GNU General Public License as published by
+** the Free Software Foundation; either version 2 of the License, or
+** (at your option) any later version.
+**
+** This program is distributed in the hope that it will be useful,
+** but WITHOUT ANY WARRANTY; without even the implied warranty of
+** MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+** GNU General Public License for more details.
+**
+** You should have received a copy of the GNU General Public License along
+** with this program; if not, write to the Free Software Foundation, Inc.,
+** 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+**
+****************************************************************************/
+
+
+use task::short::{EId, AId};
+use task::animation;
+use super::utils;
+
+use svgdom::{Document, Node, AttributeValue, ValueId};
+use svgdom::types::{Transform, FuzzyEq};
+use svgdom::types::path::{Path, Segment, SegmentData};
+
+pub fn apply_transform_to_paths(doc: &Document) {
+    let has_animation = animation::has_animation(doc);
+
+    let iter = doc.descendants().svg().filter(|n|    n.is_tag_name(EId::Path)
+                                                  && n.has_attribute(AId::Transform));
+
+    for node in iter {
+        // animations rely on the current coordinate system
+        if has_animation && animation::is_animated(&node) {
+            continue;
+        }
+
+        if !utils::is_valid_attrs(&node) {
+            continue;
+        }
+
+        let ts = utils::get_ts(&node);
+        if !is_valid_transform(&node, &ts) {
+            continue;
+        }
+
+        {
+            let mut attrs = node.attributes_mut();
+            if let Some(&mut AttributeValue::Path(ref mut path)) = attrs.get_value_mut(AId::D) {
+                transform_path(path, &ts);
+            }
+            attrs.remove(AId::Transform);
+        }
+
+        if ts.has_scale() && has_stroke(&node) {
+            // we must update 'stroke-width' if transform had scale part in it
+            let (sx, _) = ts.get_scale();
+            ::task::utils::recalc_stroke_width(&node, sx);
+        }
+    }
+}
+
+// Unlike basic shapes, path geometry can represent any affine transform,
+// so we are limited only by the stroke.
+fn is_valid_transform(node: &Node, ts: &Transform) -> bool {
+    // a degenerate matrix will collapse the path
+    if (ts.a * ts.d - ts.b * ts.c).fuzzy_eq(&0.0) {
+        return false;
+    }
+
+    // markers are scaled by the transform too
+    if has_markers(node) {
+        return false;
+    }
+
+    if !is_valid_inherited_paint(node, ts) {
+        return false;
+    }
+
+    if !has_stroke(node) {
+        return true;
+    }
+
+    // a stroke can be compensated only by the 'stroke-width' attribute,
+    // so it should be scaled evenly
+    if ts.has_skew() || (ts.has_scale() && !ts.has_proportional_scale()) {
+        return false;
+    }
+
+    // dashes are not scaled
+    if ts.has_scale() {
+        match resolve_attribute(node, AId::StrokeDasharray) {
+            None | Some(AttributeValue::PredefValue(ValueId::None)) => {}
+            _ => return false,
+        }
+    }
+
+    true
+}
+
+fn resolve_attribute(node: &Node, aid: AId) -> Option<AttributeValue> {
+    if let Some(v) = node.attribute_value(aid) {
+        return Some(v);
+    }
+
+    node.parents().find(|n| n.has_attribute(aid)).map(|n| n.attribute_value(aid).unwrap())
+}
+
+// Own paint servers are already rejected by 'is_valid_attrs', but the inherited ones
+// are still mapped using the bounding box or the user space of this path.
+fn is_valid_inherited_paint(node: &Node, ts: &Transform) -> bool {
+    [AId::Fill, AId::Stroke].iter().all(|aid| {
+        match resolve_attribute(node, *aid) {
+            Some(AttributeValue::FuncLink(ref link)) => {
+                // the bounding box is mapped exactly only by a translate
+                // and a non-mirrored uniform scale
+                   is_bbox_gradient(link)
+                && ts.b.fuzzy_eq(&0.0) && ts.c.fuzzy_eq(&0.0)
+                && ts.a > 0.0 && ts.a.fuzzy_eq(&ts.d)
+            }
+            _ => true,
+        }
+    })
+}
+
+fn is_bbox_gradient(node: &Node) -> bool {
+    if !node.is_tag_name(EId::LinearGradient) && !node.is_tag_name(EId::RadialGradient) {
+        return false;
+    }
+
+    // units can be inherited from the referenced gradient
+    if node.has_attribute(AId::XlinkHref) {
+        return false;
+    }
+
+    match node.attribute_value(AId::GradientUnits) {
+        None | Some(AttributeValue::PredefValue(ValueId::ObjectBoundingBox)) => true,
+        _ => false,
+    }
+}
+
+fn has_stroke(node: &Node) -> bool {
+    match resolve_attribute(node, AId::Stroke) {
+        // 'stroke' is 'none' by default
+        None | Some(AttributeValue::PredefValue(ValueId::None)) => false,
+        _ => true,
+    }
+}
+
+fn has_markers(node: &Node) -> bool {
+    [AId::Marker, AId::MarkerStart, AId::MarkerMid, AId::MarkerEnd].iter().any(|aid| {
+        match resolve_attribute(node, *aid) {
+            None | Some(AttributeValue::PredefValue(ValueId::None)) => false,
+            _ => true,
+        }
+    })
+}
+
+fn transform_path(path: &mut Path, ts: &Transform) {
+    path.conv_to_absolute();
+
+    // 'H' and 'V' will not stay horizontal or vertical after rotation or skew
+    conv_hv_to_lines(path);
+
+    for seg in path.d.iter_mut() {
+        let new_seg = match *seg.data() {
+            SegmentData::MoveTo { x, y } => {
+                let (x, y) = ts.apply(x, y);
+                Segment::new_move_to(x, y)
+            }
+            SegmentData::LineTo { x, y } => {
+                let (x, y) = ts.apply(x, y);
+                Segment::new_line_to(x, y)
+            }
+            SegmentData::CurveTo { x1, y1, x2, y2, x, y } => {
+                let (x1, y1) = ts.apply(x1, y1);
+                let (x2, y2) = ts.apply(x2, y2);
+                let (x, y) = ts.apply(x, y);
+                Segment::new_curve_to(x1, y1, x2, y2, x, y)
+            }
+            SegmentData::SmoothCurveTo { x2, y2, x, y } => {
+                // reflection of the control point is preserved by an affine transform
+                let (x2, y2) = ts.apply(x2, y2);
+                let (x, y) = ts.apply(x, y);
+                Segment::new_smooth_curve_to(x2, y2, x, y)
+            }
+            SegmentData::Quadratic { x1, y1, x, y } => {
+                let (x1, y1) = ts.apply(x1, y1);
+                let (x, y) = ts.apply(x, y);
+                Segment::new_quad_to(x1, y1, x, y)
+            }
+            SegmentData::SmoothQuadratic { x, y } => {
+                let (x, y) = ts.apply(x, y);
+                Segment::new_smooth_quad_to(x, y)
+            }
+            SegmentData::EllipticalArc { rx, ry, x_axis_rotation, large_arc, sweep, x, y } => {
+                let (x, y) = ts.apply(x, y);
+
+                // an arc with a zero radius is rendered as a line
+                if rx.fuzzy_eq(&0.0) || ry.fuzzy_eq(&0.0) {
+                    Segment::new_arc_to(rx, ry, x_axis_rotation, large_arc, sweep, x, y)
+                } else {
+                    let (rx, ry, angle) = transform_ellipse(rx, ry, x_axis_rotation, ts);
+
+                    // a mirrored arc goes in the opposite direction
+                    let sweep = if ts.a * ts.d - ts.b * ts.c < 0.0 { !sweep } else { sweep };
+
+                    Segment::new_arc_to(rx, ry, angle, large_arc, sweep, x, y)
+                }
+            }
+            SegmentData::ClosePath => Segment::new_close_path(),
+            SegmentData::HorizontalLineTo { .. } | SegmentData::VerticalLineTo { .. } => {
+                unreachable!()
+            }
+        };
+
+        *seg = new_seg;
+    }
+}
+
+fn conv_hv_to_lines(path: &mut Path) {
+    let mut prev = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+
+    for seg in path.d.iter_mut() {
+        match *seg.data() {
+            SegmentData::MoveTo { x, y } => {
+                start = (x, y);
+                prev = start;
+            }
+            SegmentData::HorizontalLineTo { x } => {
+                *seg = Segment::new_line_to(x, prev.1);
+                prev.0 = x;
+            }
+            SegmentData::VerticalLineTo { y } => {
+                *seg = Segment::new_line_to(prev.0, y);
+                prev.1 = y;
+            }
+            SegmentData::ClosePath => {
+                prev = start;
+            }
+            _ => {
+                prev = (seg.x().unwrap(), seg.y().unwrap());
+            }
+        }
+    }
+}
+
+// Returns radii and rotation of the ellipse after the transform.
+//
+// The ellipse is a unit circle transformed by the 'rotate(angle) scale(rx ry)'
+// matrix, so we have to find the axes of the 'ts * rotate(angle) * scale(rx ry)' matrix.
+fn transform_ellipse(rx: f64, ry: f64, angle: f64, ts: &Transform) -> (f64, f64, f64) {
+    let (sin, cos) = angle.to_radians().sin_cos();
+
+    // only the linear part of the transform affects the shape
+    let m0 = rx * (ts.a * cos + ts.c * sin);
+    let m1 = rx * (ts.b * cos + ts.d * sin);
+    let m2 = ry * (ts.c * cos - ts.a * sin);
+    let m3 = ry * (ts.d * cos - ts.b * sin);
+
+    // coefficients of the implicit equation
+    let j = m0 * m0 + m2 * m2;
+    let k = m1 * m1 + m3 * m3;
+    let jk = (j + k) / 2.0;
+
+    let d = (((m0 - m3) * (m0 - m3) + (m2 + m1) * (m2 + m1))
+           * ((m0 + m3) * (m0 + m3) + (m2 - m1) * (m2 - m1))).sqrt();
+
+    let eps = jk * 1e-10;
+
+    // the ellipse became a circle, so the rotation doesn't matter
+    if d < eps {
+        let r = jk.sqrt();
+        return (r, r, 0.0);
+    }
+
+    let l = m0 * m1 + m2 * m3;
+    let l1 = jk + d / 2.0;
+    let l2 = jk - d / 2.0;
+
+    let angle = if l.abs() < eps && (l1 - k).abs() < eps {
+        90.0
+    } else if l.abs() > (l1 - k).abs() {
+        ((l1 - j) / l).atan().to_degrees()
+    } else {
+        (l / (l1 - k)).atan().to_degrees()
+    };
+
+    if angle >= 0.0 {
+        (l1.sqrt(), l2.sqrt(), angle)
+    } else {
+        (l2.sqrt(), l1.sqrt(), angle + 90.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, WriteToString};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            base_test!($name, apply_transform_to_paths, $in_text, $out_text);
+        )
+    }
+
+    macro_rules! test_eq {
+        ($name:ident, $in_text:expr) => (
+            test!($name, $in_text, String::from_utf8_lossy($in_text));
+        )
+    }
+
+    test!(apply_1,
+b"<svg>
+    <path d='M 10 20 L 30 40' transform='translate(10 20)'/>
+</svg>",
+"<svg>
+    <path d='M 20 40 L 40 60'/>
+</svg>
+");
+
+    test!(apply_2,
+b"<svg>
+    <path d='M 10 20 h 20 v 10 z' stroke='#000000' transform='scale(2)'/>
+</svg>",
+"<svg>
+    <path d='M 20 40 L 60 40 L 60 60 Z' stroke='#000000' stroke-width='2'/>
+</svg>
+");
+
+    // non-proportional scale is allowed without a stroke
+    test!(apply_3,
+b"<svg>
+    <path d='M 10 20 C 10 30 20 30 20 20 S 30 10 30 20' transform='scale(2 3)'/>
+</svg>",
+"<svg>
+    <path d='M 20 60 C 20 90 40 90 40 60 S 60 30 60 60'/>
+</svg>
+");
+
+    test!(apply_arc_1,
+b"<svg>
+    <path d='M 0 0 A 10 5 0 0 1 20 0' transform='rotate(90)'/>
+</svg>",
+"<svg>
+    <path d='M 0 0 A 10 5 90 0 1 0 20'/>
+</svg>
+");
+
+    // radii are swapped and the arc is mirrored
+    test!(apply_arc_2,
+b"<svg>
+    <path d='M 0 0 A 10 10 0 0 1 20 0' transform='scale(-1 2)'/>
+</svg>",
+"<svg>
+    <path d='M 0 0 A 20 10 90 0 0 -20 0'/>
+</svg>
+");
+
+    // a stroke can't be scaled non-proportionally
+    test_eq!(keep_1,
+b"<svg>
+    <path d='M 10 20 L 30 40' stroke='#000000' transform='scale(2 3)'/>
+</svg>
+");
+
+    test_eq!(keep_2,
+b"<svg stroke='#000000'>
+    <path d='M 10 20 L 30 40' transform='skewX(30)'/>
+</svg>
+");
+
+    // an inherited 'objectBoundingBox' gradient is mapped exactly by a uniform scale
+    test!(apply_paint_1,
+b"<svg>
+    <linearGradient id='lg1'/>
+    <g fill='url(#lg1)'>
+        <path d='M 10 20 L 30 40' transform='translate(10) scale(2)'/>
+    </g>
+</svg>",
+"<svg>
+    <linearGradient id='lg1'/>
+    <g fill='url(#lg1)'>
+        <path d='M 30 40 L 70 80'/>
+    </g>
+</svg>
+");
+
+    test_eq!(keep_paint_1,
+b"<svg>
+    <linearGradient id='lg1'/>
+    <g fill='url(#lg1)'>
+        <path d='M 10 20 L 30 40' transform='rotate(30)'/>
+    </g>
+</svg>
+");
+
+    test_eq!(keep_paint_2,
+b"<svg>
+    <linearGradient id='lg1' gradientUnits='userSpaceOnUse'/>
+    <g fill='url(#lg1)'>
+        <path d='M 10 20 L 30 40' transform='translate(10)'/>
+    </g>
+</svg>
+");
+
+    test_eq!(keep_3,
+b"<svg>
+    <marker id='m1'/>
+    <path d='M 10 20 L 30 40' marker-start='url(#m1)' transform='translate(10)'/>
+</svg>
+");
+}
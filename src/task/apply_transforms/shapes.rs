@@ -21,6 +21,7 @@
 ****************************************************************************/
 
 use task::short::{EId, AId};
+use task::animation;
 use super::utils;
 
 use svgdom::{Document, Node, Attributes};
@@ -32,6 +33,8 @@ pub fn apply_transform_to_shapes(doc: &Document) {
     // actual shape's coordinates.
     //
     // We use own implementation, because 'task::ungroup_groups' method does not support it.
+    let has_animation = animation::has_animation(doc);
+
     let iter = doc.descendants().svg().filter(|n|    n.is_tag_name(EId::G)
                                                   && n.has_attribute(AId::Transform));
 
@@ -40,6 +43,11 @@ pub fn apply_transform_to_shapes(doc: &Document) {
             continue;
         }
 
+        // animations rely on the current coordinate system
+        if has_animation && animation::is_animated(&node) {
+            continue;
+        }
+
         // check that all children is valid
         let is_valid = node.children().svg().all(|n| {
             let flag = match n.tag_id().unwrap() {
@@ -54,6 +62,7 @@ pub fn apply_transform_to_shapes(doc: &Document) {
             && utils::has_valid_transform(&n)
             && utils::is_valid_attrs(&n)
             && utils::is_valid_coords(&n)
+            && !(has_animation && animation::is_animated(&n))
         });
 
         if is_valid {
@@ -82,6 +91,10 @@ pub fn apply_transform_to_shapes(doc: &Document) {
     // apply transform to shapes
     let iter = doc.descendants().svg().filter(|n| n.has_attribute(AId::Transform));
     for node in iter {
+        if has_animation && animation::is_animated(&node) {
+            continue;
+        }
+
         match node.tag_id().unwrap() {
             EId::Rect => process_rect(&node),
             EId::Circle => process_circle(&node),
@@ -289,4 +302,12 @@ b"<svg>
 "
 );
 
+    test_eq!(keep_animated_1,
+b"<svg>
+    <rect height='10' width='10' x='10' y='10' transform='translate(10 20)'>
+        <animate attributeName='x' to='20'/>
+    </rect>
+</svg>
+"
+);
 }
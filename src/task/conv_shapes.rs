@@ -21,6 +21,7 @@
 ****************************************************************************/
 
 use super::short::{EId, AId, Unit};
+use super::animation;
 
 use svgdom::{Document, Node, Attribute, AttributeValue};
 use svgdom::types::Length;
@@ -33,7 +34,14 @@ use svgdom::types::path;
 ///
 /// We should run it before path processing.
 pub fn convert_shapes_to_paths(doc: &Document) {
+    let has_animation = animation::has_animation(doc);
+
     for node in doc.descendants().svg() {
+        // animated attributes will be lost
+        if has_animation && animation::is_animated(&node) {
+            continue;
+        }
+
         // descendants() iterates only over svg elements, which all have a tag name
         match node.tag_id().unwrap() {
             EId::Line => convert_line(&node),
@@ -24,6 +24,7 @@ use std::fmt;
 use std::cmp;
 
 use super::short::{EId, AId};
+use super::scripting;
 
 use svgdom::{Document, Node, Attribute, AttributeType};
 
@@ -322,10 +323,11 @@ mod table_tests {
 // TODO: use 'svg' instead of 'g' when possible
 
 pub fn group_by_style(doc: &Document) {
-    _group_by_style(&doc.svg_element().unwrap());
+    let has_scripts = scripting::has_scripts(doc);
+    _group_by_style(&doc.svg_element().unwrap(), has_scripts);
 }
 
-fn _group_by_style(parent: &Node) {
+fn _group_by_style(parent: &Node, has_scripts: bool) {
     let mut node_list = Vec::with_capacity(16);
 
     // we can reuse an existing group only if all children are valid
@@ -347,7 +349,15 @@ fn _group_by_style(parent: &Node) {
 
         // recursive processing
         if node.is_tag_name(EId::G) {
-            _group_by_style(&node);
+            _group_by_style(&node, has_scripts);
+        }
+
+        // Elements with event attributes and their parents should not be moved,
+        // because scripts can depend on the tree structure.
+        if has_scripts && scripting::is_scripted(&node) {
+            is_all_children = false;
+            node_list.clear();
+            continue;
         }
 
         node_list.push(node);
@@ -21,6 +21,7 @@
 ****************************************************************************/
 
 use super::short::{EId, AId};
+use super::scripting;
 
 use svgdom::{Document, Node, AttributeValue};
 
@@ -46,6 +47,11 @@ static RG_ATTRIBUTES: &'static [AId] = &[
 ];
 
 pub fn merge_gradients(doc: &Document) {
+    // scripts can reference merged gradients by id
+    if scripting::has_scripts(doc) {
+        return;
+    }
+
     let mut nodes = Vec::with_capacity(16);
 
     loop {
@@ -32,7 +32,7 @@ pub use self::group_by_style::group_by_style;
 pub use self::group_defs::group_defs;
 pub use self::join_style_attrs::join_style_attributes;
 pub use self::merge_gradients::merge_gradients;
-pub use self::preclean_checks::preclean_checks;
+pub use self::preclean_checks::{preclean_checks, collect_diagnostics, check_style_sheets};
 pub use self::rm_default_attrs::remove_default_attributes;
 pub use self::rm_dupl_defs::{
     remove_dupl_linear_gradients,
@@ -40,6 +40,7 @@ pub use self::rm_dupl_defs::{
     remove_dupl_fe_gaussian_blur,
 };
 pub use self::regroup_gradient_stops::regroup_gradient_stops;
+pub use self::resolve_switch::resolve_switch;
 pub use self::resolve_use::resolve_use;
 pub use self::rm_elems::remove_element;
 pub use self::rm_gradient_attrs::remove_gradient_attributes;
@@ -51,12 +52,14 @@ pub use self::rm_unref_ids::remove_unreferenced_ids;
 pub use self::rm_unused_coords::remove_unused_coordinates;
 pub use self::rm_unused_defs::remove_unused_defs;
 pub use self::rm_version::remove_version;
+pub use self::round_coords::round_coordinates;
 pub use self::trim_ids::trim_ids;
 pub use self::ungroup_defs::ungroup_defs;
 pub use self::ungroup_groups::ungroup_groups;
 
 #[macro_use]
 mod macros;
+mod animation;
 mod conv_shapes;
 mod final_fixes;
 mod fix_attrs;
@@ -66,6 +69,7 @@ mod join_style_attrs;
 mod merge_gradients;
 mod preclean_checks;
 mod regroup_gradient_stops;
+mod resolve_switch;
 mod resolve_use;
 mod rm_default_attrs;
 mod rm_dupl_defs;
@@ -79,6 +83,8 @@ mod rm_unref_ids;
 mod rm_unused_coords;
 mod rm_unused_defs;
 mod rm_version;
+mod round_coords;
+mod scripting;
 mod trim_ids;
 mod ungroup_defs;
 mod ungroup_groups;
@@ -94,7 +100,8 @@ mod short {
 
 pub mod utils {
     use svgdom::{Document, Node, AttributeValue};
-    use task::short::AId;
+    use svgdom::types::Transform;
+    use task::short::{AId, Unit};
     use svgdom_utils;
 
     pub fn resolve_gradient_attributes(doc: &Document) -> Result<(), svgdom_utils::Error> {
@@ -104,6 +111,78 @@ pub mod utils {
         Ok(())
     }
 
+    /// Checks that node has an external `xlink:href`, like `sprite.svg#icon`.
+    ///
+    /// Such links are stored as strings and should be preserved as is.
+    pub fn has_external_link(node: &Node) -> bool {
+        match node.attributes().get_value(AId::XlinkHref) {
+            Some(&AttributeValue::String(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the root element viewport size in user units
+    /// and the size of a user unit in pixels.
+    ///
+    /// The `viewBox` attribute is used first and then `width` and `height`.
+    /// A user unit is a pixel when the `viewBox`, `width` or `height` attribute is not set,
+    /// or when `width` and `height` have units.
+    pub fn viewport_size(doc: &Document) -> Option<(f64, f64, f64)> {
+        let svg = doc.svg_element()?;
+        let attrs = svg.attributes();
+
+        let size = match (attrs.get_value(AId::Width), attrs.get_value(AId::Height)) {
+            (Some(&AttributeValue::Length(w)), Some(&AttributeValue::Length(h))) => {
+                // we can't resolve relative units
+                if w.unit == Unit::None && h.unit == Unit::None && w.num > 0.0 && h.num > 0.0 {
+                    Some((w.num, h.num))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        let view_box = match attrs.get_value(AId::ViewBox) {
+            Some(&AttributeValue::NumberList(ref list)) if list.len() == 4 => {
+                if list[2] > 0.0 && list[3] > 0.0 { Some((list[2], list[3])) } else { None }
+            }
+            _ => None,
+        };
+
+        match (size, view_box) {
+            (Some((w, h)), Some((vw, vh))) => Some((vw, vh, (w / vw).max(h / vh))),
+            (None, Some((vw, vh))) => Some((vw, vh, 1.0)),
+            (Some((w, h)), None) => Some((w, h, 1.0)),
+            (None, None) => None,
+        }
+    }
+
+    /// Returns the node transform with all the parents transforms applied.
+    pub fn abs_transform(node: &Node) -> Transform {
+        let mut nodes: Vec<Node> = node.parents().collect();
+        nodes.reverse();
+        nodes.push(node.clone());
+
+        let mut ts = Transform::default();
+        for n in nodes {
+            if let Some(AttributeValue::Transform(t)) = n.attribute_value(AId::Transform) {
+                ts.append(&t);
+            }
+        }
+
+        ts
+    }
+
+    /// Returns the maximum and the minimum scale factors of the transform.
+    pub fn singular_values(ts: &Transform) -> (f64, f64) {
+        let t = ts.a * ts.a + ts.b * ts.b + ts.c * ts.c + ts.d * ts.d;
+        let det = ts.a * ts.d - ts.b * ts.c;
+        let diff = (t * t - 4.0 * det * det).max(0.0).sqrt();
+
+        (((t + diff) / 2.0).sqrt(), ((t - diff) / 2.0).max(0.0).sqrt())
+    }
+
     pub fn recalc_stroke_width(node: &Node, scale_factor: f64) {
         // resolve current 'stroke-width'
         let stroke_width_val = if let Some(attr) = node.attribute(AId::StrokeWidth) {
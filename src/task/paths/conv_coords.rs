@@ -0,0 +1,263 @@
+/****************************************************************************
+**
+** svgcleaner could help you to clean up your SVG files
+** from unnecessary data.
+** Copyright (C) 2012-2017 Evgeniy Reizner
+**
+** This program is free software; you can redistribute it and/or modify
+** it under the terms of the GNU General Public License as published by
+** the Free Software Foundation; either version 2 of the License, or
+** (at your option) any later version.
+**
+** This program is distributed in the hope that it will be useful,
+** but WITHOUT ANY WARRANTY; without even the implied warranty of
+** MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+** GNU General Public License for more details.
+**
+** You should have received a copy of the GNU General Public License along
+** with this program; if not, write to the Free Software Foundation, Inc.,
+** 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+**
+****************************************************************************/
+
+
+use svgdom::WriteOptions;
+use svgdom::types::path::{Path, Segment, SegmentData, Command};
+
+// A relative segment is usually shorter, but not always. For example, 'L 0 0' after
+// 'M 100 100' is shorter than 'l -100 -100'.
+//
+// We can't serialize the whole path for each variant, because it's too slow,
+// so we estimate the length of each segment in both forms, taking the previous segment
+// into account, and choose the shortest combination.
+
+/// Converts each segment of an absolute path into an absolute or relative one,
+/// depending on what is shorter.
+pub fn conv_to_shortest(path: &mut Path, opt: &WriteOptions) {
+    if path.d.is_empty() {
+        return;
+    }
+
+    let abs = path.d.clone();
+    let mut rel = path.clone();
+    rel.conv_to_relative();
+    let rel = rel.d;
+
+    // index 0 - the absolute segment, index 1 - the relative one
+    let variants = |i: usize| [&abs[i], &rel[i]];
+
+    // the shortest length of the path that ends with the specified variant
+    let mut costs = [segment_len(&abs[0], None, opt), segment_len(&rel[0], None, opt)];
+    // the variant of the previous segment for each variant of the current one
+    let mut back = Vec::with_capacity(path.d.len());
+    back.push([0, 0]);
+
+    for i in 1..path.d.len() {
+        let prev = variants(i - 1);
+        let mut new_costs = [0; 2];
+        let mut new_back = [0; 2];
+
+        for (k, seg) in variants(i).iter().enumerate() {
+            let from_abs = costs[0] + segment_len(seg, Some(prev[0]), opt);
+            let from_rel = costs[1] + segment_len(seg, Some(prev[1]), opt);
+
+            if from_rel <= from_abs {
+                new_costs[k] = from_rel;
+                new_back[k] = 1;
+            } else {
+                new_costs[k] = from_abs;
+                new_back[k] = 0;
+            }
+        }
+
+        costs = new_costs;
+        back.push(new_back);
+    }
+
+    // prefer relative segments, like before
+    let mut k = if costs[1] <= costs[0] { 1 } else { 0 };
+    for i in (0..path.d.len()).rev() {
+        path.d[i] = *variants(i)[k];
+        k = back[i][k];
+    }
+}
+
+// Estimates a segment length, as it will be written by 'svgdom'.
+fn segment_len(seg: &Segment, prev: Option<&Segment>, opt: &WriteOptions) -> usize {
+    let mut len = 0;
+
+    let is_cmd_skipped = match prev {
+        Some(prev) if prev.absolute == seg.absolute => {
+            let prev_cmd = prev.cmd();
+            let cmd = seg.cmd();
+
+               (opt.paths.remove_duplicated_commands
+                && prev_cmd == cmd
+                && cmd != Command::MoveTo
+                && cmd != Command::ClosePath)
+            || (opt.paths.use_implicit_lineto_commands
+                && prev_cmd == Command::MoveTo
+                && cmd == Command::LineTo)
+        }
+        _ => false,
+    };
+
+    if !is_cmd_skipped {
+        if prev.is_some() && !opt.paths.use_compact_notation {
+            len += 1;
+        }
+
+        len += 1;
+    }
+
+    let mut prev_num = if is_cmd_skipped {
+        prev.and_then(|p| numbers(p, opt).pop())
+    } else {
+        None
+    };
+
+    for num in numbers(seg, opt) {
+        if let Some(ref prev_num) = prev_num {
+            if !is_separator_skipped(prev_num, &num, opt) {
+                len += 1;
+            }
+        } else if !opt.paths.use_compact_notation {
+            // a space after the command
+            len += 1;
+        }
+
+        len += num.len();
+        prev_num = Some(num);
+    }
+
+    len
+}
+
+fn is_separator_skipped(prev: &str, num: &str, opt: &WriteOptions) -> bool {
+    if !opt.paths.use_compact_notation {
+        return false;
+    }
+
+       num.starts_with('-')
+    || (num.starts_with('.') && prev.contains('.'))
+    || (opt.paths.join_arc_to_flags && (prev == "0" || prev == "1") && num.len() == 1)
+}
+
+fn numbers(seg: &Segment, opt: &WriteOptions) -> Vec<String> {
+    let fmt = |n: f64| format_num(n, opt);
+    let flag = |f: bool| if f { "1".to_string() } else { "0".to_string() };
+
+    match *seg.data() {
+          SegmentData::MoveTo { x, y }
+        | SegmentData::LineTo { x, y }
+        | SegmentData::SmoothQuadratic { x, y } => vec![fmt(x), fmt(y)],
+        SegmentData::HorizontalLineTo { x } => vec![fmt(x)],
+        SegmentData::VerticalLineTo { y } => vec![fmt(y)],
+        SegmentData::CurveTo { x1, y1, x2, y2, x, y } => {
+            vec![fmt(x1), fmt(y1), fmt(x2), fmt(y2), fmt(x), fmt(y)]
+        }
+          SegmentData::SmoothCurveTo { x2: x1, y2: y1, x, y }
+        | SegmentData::Quadratic { x1, y1, x, y } => vec![fmt(x1), fmt(y1), fmt(x), fmt(y)],
+        SegmentData::EllipticalArc { rx, ry, x_axis_rotation, large_arc, sweep, x, y } => {
+            vec![fmt(rx), fmt(ry), fmt(x_axis_rotation), flag(large_arc), flag(sweep),
+                 fmt(x), fmt(y)]
+        }
+        SegmentData::ClosePath => Vec::new(),
+    }
+}
+
+fn format_num(num: f64, opt: &WriteOptions) -> String {
+    let p = 10f64.powi(opt.paths.coordinates_precision as i32);
+    let num = (num * p).round() / p;
+
+    // '-0' is written as '0'
+    let mut s = if num == 0.0 { "0".to_string() } else { num.to_string() };
+
+    if opt.remove_leading_zero {
+        if s.starts_with("0.") {
+            s.remove(0);
+        } else if s.starts_with("-0.") {
+            s.remove(1);
+        }
+    }
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{FromStream, WriteOptions, WriteBuffer};
+    use svgdom::types::path::Path;
+
+    macro_rules! test {
+        ($name:ident, $dupl_cmd:expr, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut opt = WriteOptions::default();
+                opt.paths.use_compact_notation = false;
+                opt.paths.remove_duplicated_commands = $dupl_cmd;
+                opt.paths.use_implicit_lineto_commands = false;
+                opt.paths.coordinates_precision = 8;
+
+                let mut path = Path::from_data($in_text).unwrap();
+                path.conv_to_absolute();
+                conv_to_shortest(&mut path, &opt);
+                assert_eq_text!(path.to_string(), $out_text);
+            }
+        )
+    }
+
+    test!(conv_1, false, b"M 10 10 L 20 20",
+                         "m 10 10 l 10 10");
+
+    test!(conv_2, false, b"M 100 100 L 0 0 L 100 1",
+                         "m 100 100 L 0 0 l 100 1");
+
+    // a command of the absolute segment can be skipped
+    test!(conv_3, true, b"M 100 100 L 0 0 L 100 1",
+                        "m 100 100 L 0 0 L 100 1");
+
+    test!(conv_4, false, b"M 100 100 H 0 V 200 Z",
+                         "m 100 100 H 0 v 100 z");
+
+    fn path_len(path: &Path, opt: &WriteOptions) -> usize {
+        let mut buf = Vec::new();
+        path.write_buf_opt(opt, &mut buf);
+        buf.len()
+    }
+
+    // the result must not be longer than the pure absolute and relative paths,
+    // when written with the default options
+    macro_rules! test_len {
+        ($name:ident, $in_text:expr) => (
+            #[test]
+            fn $name() {
+                let opt = WriteOptions::default();
+
+                let mut abs = Path::from_data($in_text).unwrap();
+                abs.conv_to_absolute();
+
+                let mut rel = abs.clone();
+                rel.conv_to_relative();
+
+                let mut path = abs.clone();
+                conv_to_shortest(&mut path, &opt);
+
+                let len = path_len(&path, &opt);
+                assert!(len <= path_len(&abs, &opt));
+                assert!(len <= path_len(&rel, &opt));
+            }
+        )
+    }
+
+    test_len!(conv_len_1, b"M 10 10 L 20 20");
+
+    test_len!(conv_len_2, b"M 100 100 L 0 0 L 100 1 L 100 1");
+
+    test_len!(conv_len_3, b"M 100 100 H 0 V 200 Z M 150 150 h 10 v 10 z");
+
+    test_len!(conv_len_4, b"M 10.5 -20.25 C 11 22 -30 40 105.125 200 S 0 0 -10 -10.5");
+
+    test_len!(conv_len_5, b"M 0 0 A 10 10 0 0 1 20 0 Q 100 100 200 0 T 400 0 L 0.5 0.5");
+}
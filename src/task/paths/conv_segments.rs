@@ -27,7 +27,13 @@ use svgdom::types::{FuzzyEq, FuzzyOrd};
 
 use super::utils;
 
-pub fn convert_segments(path: &mut Path) {
+/// Converts segments into shorter ones.
+///
+/// `precision` is the amount of digits after the decimal point in the output.
+/// Conversions that change the geometry less than by this precision are allowed.
+pub fn convert_segments(path: &mut Path, precision: u8) {
+    let tolerance = 10f64.powi(-(precision as i32));
+
     // repeat until we have any changes
     let mut is_changed = true;
     while is_changed {
@@ -38,15 +44,31 @@ pub fn convert_segments(path: &mut Path) {
             break;
         }
 
-        _convert_segments(path, &mut is_changed);
+        _convert_segments(path, tolerance, &mut is_changed);
     }
 }
 
-fn _convert_segments(path: &mut Path, is_changed: &mut bool) {
+fn _convert_segments(path: &mut Path, tolerance: f64, is_changed: &mut bool) {
+    let is_near = |a: f64, b: f64| (a - b).abs() <= tolerance;
+
+    // a control point of the previous Quadratic or SmoothQuadratic segment
+    let mut prev_quad: Option<(f64, f64)> = None;
+
     let mut i = 1;
     while i < path.d.len() {
         let (prev_x, prev_y) = utils::resolve_xy(path, i - 1);
         let prev_seg = path.d[i - 1];
+        // the control point that SmoothQuadratic will use
+        let (quad_x1, quad_y1) = match prev_quad {
+            Some((x1, y1)) => (prev_x * 2.0 - x1, prev_y * 2.0 - y1),
+            None => (prev_x, prev_y),
+        };
+        // smooth segments depend on the previous control point, so it should not be changed
+        let is_next_smooth = match path.d.get(i + 1).map(|s| *s.data()) {
+              Some(SegmentData::SmoothCurveTo { .. })
+            | Some(SegmentData::SmoothQuadratic { .. }) => true,
+            _ => false,
+        };
         let curr_seg = &mut path.d[i];
         match *curr_seg.data() {
             SegmentData::LineTo { x, y } => {
@@ -88,6 +110,12 @@ fn _convert_segments(path: &mut Path, is_changed: &mut bool) {
                     && is_point_on_line(prev_x, prev_y, x, y, x2, y2)
                 };
 
+                let quad = if is_next_smooth {
+                    None
+                } else {
+                    cubic_to_quad(prev_x, prev_y, curr_seg.data(), tolerance)
+                };
+
                 if is_vlineto() {
                     *curr_seg = Segment::new_vline_to(y);
                     *is_changed = true;
@@ -97,6 +125,9 @@ fn _convert_segments(path: &mut Path, is_changed: &mut bool) {
                 } else if is_lineto() {
                     *curr_seg = Segment::new_line_to(x, y);
                     *is_changed = true;
+                } else if let Some((qx, qy)) = quad {
+                    *curr_seg = Segment::new_quad_to(qx, qy, x, y);
+                    *is_changed = true;
                 } else {
                     let (nx1, ny1) = match *prev_seg.data() {
                           SegmentData::CurveTo { x2: px2, y2: py2, x: px, y: py, .. }
@@ -114,15 +145,47 @@ fn _convert_segments(path: &mut Path, is_changed: &mut bool) {
                     }
                 }
             }
-            // TODO: CurveTo -> Quadratic
-            // TODO: Quadratic -> SmoothQuadTo
+            SegmentData::Quadratic { x1, y1, x, y } => {
+                if is_near(x1, quad_x1) && is_near(y1, quad_y1) {
+                    *curr_seg = Segment::new_smooth_quad_to(x, y);
+                    *is_changed = true;
+                }
+            }
             _ => {}
         }
 
+        prev_quad = match *path.d[i].data() {
+            SegmentData::Quadratic { x1, y1, .. } => Some((x1, y1)),
+            SegmentData::SmoothQuadratic { .. } => Some((quad_x1, quad_y1)),
+            _ => None,
+        };
+
         i += 1;
     }
 }
 
+// Checks that CurveTo is a degree-elevated Quadratic and returns its control point.
+//
+// Control points of such curve are placed at 2/3 of the way
+// from the end points to the quadratic control point.
+fn cubic_to_quad(px: f64, py: f64, seg: &SegmentData, tolerance: f64) -> Option<(f64, f64)> {
+    let (x1, y1, x2, y2, x, y) = match *seg {
+        SegmentData::CurveTo { x1, y1, x2, y2, x, y } => (x1, y1, x2, y2, x, y),
+        _ => return None,
+    };
+
+    let qx1 = (3.0 * x1 - px) / 2.0;
+    let qy1 = (3.0 * y1 - py) / 2.0;
+    let qx2 = (3.0 * x2 - x) / 2.0;
+    let qy2 = (3.0 * y2 - y) / 2.0;
+
+    if (qx1 - qx2).abs() <= tolerance && (qy1 - qy2).abs() <= tolerance {
+        Some(((qx1 + qx2) / 2.0, (qy1 + qy2) / 2.0))
+    } else {
+        None
+    }
+}
+
 fn is_point_on_line(x1: f64, y1: f64, x2: f64, y2: f64, x: f64, y: f64) -> bool
 {
     // check that point is actually on line
@@ -170,11 +233,14 @@ mod tests {
 
     macro_rules! test {
         ($name:ident, $in_text:expr, $out_text:expr) => (
+            test!($name, 8, $in_text, $out_text);
+        );
+        ($name:ident, $precision:expr, $in_text:expr, $out_text:expr) => (
             #[test]
             fn $name() {
                 let mut path = Path::from_data($in_text).unwrap();
                 path.conv_to_absolute();
-                convert_segments(&mut path);
+                convert_segments(&mut path, $precision);
                 assert_eq_text!(path.to_string(), $out_text);
             }
         )
@@ -217,4 +283,30 @@ mod tests {
 
     test!(conv_cl_2, b"M 10,15 C 10,15 72.5,10 72.5,55 C 72.5,100 135,100 135,55 L 10,55",
                       "M 10 15 S 72.5 10 72.5 55 S 135 100 135 55 H 10");
+
+    test!(conv_cq_1, b"M 0 0 C 20 40 40 40 60 0",
+                      "M 0 0 Q 30 60 60 0");
+
+    test!(conv_cq_2, b"M 0 0 C 20 40 40 40 60 0 C 80 -40 100 -40 120 0",
+                      "M 0 0 Q 30 60 60 0 T 120 0");
+
+    // the next segment depends on the CurveTo control point
+    test!(conv_cq_3, b"M 0 0 C 20 40 40 40 60 0 S 100 -40 120 0",
+                      "M 0 0 C 20 40 40 40 60 0 S 100 -40 120 0");
+
+    // control points are rounded, but still within the coordinates precision
+    test!(conv_cq_4, 3, b"M 0 0 C 20.0002 40 39.9998 40 60 0",
+                         "M 0 0 Q 30 60 60 0");
+
+    test!(conv_cq_5, 8, b"M 0 0 C 20.0002 40 39.9998 40 60 0",
+                         "M 0 0 C 20.0002 40 39.9998 40 60 0");
+
+    test!(conv_qt_1, b"M 0 0 Q 30 60 60 0 Q 90 -60 120 0",
+                      "M 0 0 Q 30 60 60 0 T 120 0");
+
+    test!(conv_qt_2, b"M 0 0 Q 30 60 60 0 T 120 0 Q 150 60 180 0",
+                      "M 0 0 Q 30 60 60 0 T 120 0 T 180 0");
+
+    test!(conv_qt_3, 3, b"M 0 0 Q 30 60 60 0 Q 90.0004 -60 120 0",
+                         "M 0 0 Q 30 60 60 0 T 120 0");
 }
@@ -20,51 +20,92 @@
 **
 ****************************************************************************/
 
-use svgdom::{Document, AttributeValue};
+use svgdom::{Document, AttributeValue, WriteOptions};
 use svgdom::types::path::Path;
 
 use task::short::{EId, AId};
-use options::Options;
+use task::animation;
+use options::{Options, Tolerance};
 
+mod conv_coords;
 mod conv_segments;
 mod rm_unused;
+mod simplify;
+
+pub fn process_paths(doc: &Document, options: &Options, write_opt: &WriteOptions) {
+    let has_animation = animation::has_animation(doc);
+
+    // 'simplify_path' converts segments into the explicit ones,
+    // so it relies on 'convert_segments' to restore them
+    let is_simplify = options.simplify_paths && options.convert_segments;
+    let viewport = ::task::utils::viewport_size(doc).map(|(w, h, _)| w.max(h));
 
-pub fn process_paths(doc: &Document, options: &Options) {
     for node in doc.descendants().svg().filter(|n| n.is_tag_name(EId::Path)) {
+        // Path animation requires the same segments in all values,
+        // so we can't change them.
+        if has_animation && animation::is_attribute_animated(&node, AId::D) {
+            continue;
+        }
+
         // We can't process paths with marker, because if we remove all segments
         // it will break rendering.
         // TODO: do not remove first segment if node has marker
         let has_marker = node.has_attributes(&[AId::Marker, AId::MarkerStart,
                                                AId::MarkerMid, AId::MarkerEnd]);
 
+        let tolerance = if is_simplify {
+            match options.simplify_paths_tolerance {
+                Tolerance::UserUnits(v) => Some(v),
+                // A percentage is set in the root element user units, so it should be
+                // scaled down by the path transform. We can't simplify paths if the viewport
+                // size is unknown.
+                Tolerance::Viewport(v) => viewport.and_then(|viewport| {
+                    let ts = ::task::utils::abs_transform(&node);
+                    let (scale, _) = ::task::utils::singular_values(&ts);
+                    if scale > 0.0 { Some(viewport * v / 100.0 / scale) } else { None }
+                }),
+            }
+        } else {
+            None
+        };
+
         let mut attrs = node.attributes_mut();
         if let Some(&mut AttributeValue::Path(ref mut path)) = attrs.get_value_mut(AId::D) {
-            process_path(path, has_marker, options);
+            process_path(path, has_marker, tolerance, options, write_opt);
         }
     }
 }
 
-fn process_path(path: &mut Path, has_marker: bool, options: &Options) {
+fn process_path(path: &mut Path, has_marker: bool, tolerance: Option<f64>,
+                options: &Options, write_opt: &WriteOptions) {
     path.conv_to_absolute();
 
+    if let Some(tolerance) = tolerance {
+        // markers are rendered at each vertex, so we can't remove them
+        if !has_marker {
+            simplify::simplify_path(path, tolerance);
+        }
+    }
+
     if options.convert_segments {
-        conv_segments::convert_segments(path);
+        conv_segments::convert_segments(path, write_opt.paths.coordinates_precision);
     }
 
     if options.remove_unused_segments && !has_marker {
         rm_unused::remove_unused_segments(path);
     }
 
-    path.conv_to_relative();
+    conv_coords::conv_to_shortest(path, write_opt);
+}
+
+/// Converts segments of an absolute path and chooses the form of each of them again,
+/// after its coordinates were changed by the coordinates rounding.
+pub fn reprocess_path(path: &mut Path, options: &Options, write_opt: &WriteOptions) {
+    if options.convert_segments {
+        conv_segments::convert_segments(path, write_opt.paths.coordinates_precision);
+    }
 
-    // TODO: estimate path length
-    // NOTE: A relative path can be bigger, but usually only on few chars.
-    //       But to check it - we need to convert an original and a new path to strings
-    //       and compare theme. And in case that new path is bigger - fallback to original,
-    //       so we need a copy of original path too. It will be extremely slow. Straightforward
-    //       implementation gives us 2x slowdown of the whole application time.
-    //       Which is unacceptable.
-    //       So we sacrifice a very small amount of cleaning ratio for performance benefits.
+    conv_coords::conv_to_shortest(path, write_opt);
 }
 
 mod utils {
@@ -111,3 +152,45 @@ mod utils {
         (resolve_x(path, start), resolve_y(path, start))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::Document;
+
+    fn segments_count(in_text: &[u8]) -> usize {
+        let doc = Document::from_data(in_text).unwrap();
+
+        let mut opt = Options::default();
+        opt.simplify_paths = true;
+        opt.simplify_paths_tolerance = Tolerance::Viewport(1.0);
+        process_paths(&doc, &opt, &WriteOptions::default());
+
+        let node = doc.descendants().svg().find(|n| n.is_tag_name(EId::Path)).unwrap();
+        match node.attribute_value(AId::D) {
+            Some(AttributeValue::Path(path)) => path.d.len(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn simplify_tolerance_1() {
+        let count = segments_count(
+b"<svg viewBox='0 0 100 100'>
+    <path d='M 0 0 L 5 0.2 L 10 0'/>
+</svg>");
+        assert_eq!(count, 2);
+    }
+
+    // a percentage is scaled down by the parents transforms
+    #[test]
+    fn simplify_tolerance_2() {
+        let count = segments_count(
+b"<svg viewBox='0 0 100 100'>
+    <g transform='scale(100)'>
+        <path d='M 0 0 L 5 0.2 L 10 0'/>
+    </g>
+</svg>");
+        assert_eq!(count, 3);
+    }
+}
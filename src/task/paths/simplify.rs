@@ -0,0 +1,372 @@
+/****************************************************************************
+**
+** svgcleaner could help you to clean up your SVG files
+** from unnecessary data.
+** Copyright (C) 2012-2017 Evgeniy Reizner
+**
+** This program is free software; you can redistribute it and/or modify
+** it under the terms of the GNU General Public License as published by
+** the Free Software Foundation; either version 2 of the License, or
+** (at your option) any later version.
+**
+** This program is distributed in the hope that it will be useful,
+** but WITHOUT ANY WARRANTY; without even the implied warranty of
+** MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+** GNU General Public License for more details.
+**
+** You should have received a copy of the GNU General Public License along
+** with this program; if not, write to the Free Software Foundation, Inc.,
+** 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+**
+****************************************************************************/
+
+
+use svgdom::types::path::{Path, Segment, SegmentData};
+
+type Point = (f64, f64);
+
+// Amount of points used to compare curves.
+const CURVE_SAMPLES: usize = 32;
+const FIT_SAMPLES: usize = 64;
+
+/// Simplifies an absolute path, so it will not deviate from the original one
+/// more than by `tolerance`.
+///
+/// Each replacement is checked against the original geometry it replaces.
+pub fn simplify_path(path: &mut Path, tolerance: f64) {
+    if tolerance <= 0.0 {
+        return;
+    }
+
+    conv_to_explicit(path);
+
+    // Curves converted into lines can be simplified again by 'simplify_lines',
+    // so each of them can use only a half of the tolerance.
+    curves_to_lines(path, tolerance / 2.0);
+    merge_curves(path, tolerance);
+    simplify_lines(path, tolerance / 2.0);
+}
+
+// Returns an end point of each segment.
+fn end_points(path: &Path) -> Vec<Point> {
+    let mut list = Vec::with_capacity(path.d.len());
+    let mut prev = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+
+    for seg in &path.d {
+        let p = match *seg.data() {
+            SegmentData::MoveTo { x, y } => {
+                start = (x, y);
+                start
+            }
+            SegmentData::HorizontalLineTo { x } => (x, prev.1),
+            SegmentData::VerticalLineTo { y } => (prev.0, y),
+            SegmentData::ClosePath => start,
+            _ => (seg.x().unwrap(), seg.y().unwrap()),
+        };
+
+        list.push(p);
+        prev = p;
+    }
+
+    list
+}
+
+// Converts 'H' and 'V' into 'L', 'S' into 'C' and 'T' into 'Q',
+// so segments will not depend on each other.
+//
+// 'convert_segments' will restore them later.
+fn conv_to_explicit(path: &mut Path) {
+    let points = end_points(path);
+
+    for i in 1..path.d.len() {
+        let prev = points[i - 1];
+        let prev_seg = *path.d[i - 1].data();
+
+        let new_seg = match *path.d[i].data() {
+              SegmentData::HorizontalLineTo { .. }
+            | SegmentData::VerticalLineTo { .. } => {
+                Segment::new_line_to(points[i].0, points[i].1)
+            }
+            SegmentData::SmoothCurveTo { x2, y2, x, y } => {
+                let (x1, y1) = match prev_seg {
+                    // the previous segment is already converted
+                    SegmentData::CurveTo { x2: px2, y2: py2, .. } => reflect((px2, py2), prev),
+                    _ => prev,
+                };
+
+                Segment::new_curve_to(x1, y1, x2, y2, x, y)
+            }
+            SegmentData::SmoothQuadratic { x, y } => {
+                let (x1, y1) = match prev_seg {
+                    SegmentData::Quadratic { x1: px1, y1: py1, .. } => reflect((px1, py1), prev),
+                    _ => prev,
+                };
+
+                Segment::new_quad_to(x1, y1, x, y)
+            }
+            _ => continue,
+        };
+
+        path.d[i] = new_seg;
+    }
+}
+
+// Converts curves which control points are close to the chord into lines.
+//
+// A curve lies inside the convex hull of its control points,
+// so it will not deviate from the chord more than the control points.
+// We are using a distance to the chord and not to the line,
+// because a curve with control points outside the chord can go back.
+fn curves_to_lines(path: &mut Path, tolerance: f64) {
+    let points = end_points(path);
+
+    for i in 1..path.d.len() {
+        let p0 = points[i - 1];
+
+        let is_line = match *path.d[i].data() {
+            SegmentData::CurveTo { x1, y1, x2, y2, x, y } => {
+                   dist_to_segment((x1, y1), p0, (x, y)) <= tolerance
+                && dist_to_segment((x2, y2), p0, (x, y)) <= tolerance
+            }
+            SegmentData::Quadratic { x1, y1, x, y } => {
+                dist_to_segment((x1, y1), p0, (x, y)) <= tolerance
+            }
+            _ => false,
+        };
+
+        if is_line {
+            path.d[i] = Segment::new_line_to(points[i].0, points[i].1);
+        }
+    }
+}
+
+// Replaces pairs of subsequent curves with a single one, when possible.
+fn merge_curves(path: &mut Path, tolerance: f64) {
+    let mut points = end_points(path);
+
+    // points of the original curves, which are covered by each segment
+    let mut samples: Vec<Vec<Point>> = (0..path.d.len()).map(|i| {
+        match *path.d[i].data() {
+            SegmentData::CurveTo { x1, y1, x2, y2, x, y } if i > 0 => {
+                sample_curve(points[i - 1], (x1, y1), (x2, y2), (x, y), CURVE_SAMPLES)
+            }
+            _ => Vec::new(),
+        }
+    }).collect();
+
+    let mut i = 1;
+    while i + 1 < path.d.len() {
+        let seg = match (*path.d[i].data(), *path.d[i + 1].data()) {
+            (SegmentData::CurveTo { x1, y1, x2, y2, .. },
+             SegmentData::CurveTo { x1: x4, y1: y4, x2: x5, y2: y5, x: x6, y: y6 }) => {
+                let curve1 = [points[i - 1], (x1, y1), (x2, y2), points[i]];
+                let curve2 = [points[i], (x4, y4), (x5, y5), (x6, y6)];
+                fit_curve(&curve1, &curve2, &samples[i], &samples[i + 1], tolerance)
+            }
+            _ => None,
+        };
+
+        if let Some(seg) = seg {
+            path.d[i] = seg;
+            path.d.remove(i + 1);
+            points.remove(i);
+
+            let next_samples = samples.remove(i + 1);
+            samples[i].extend(next_samples.into_iter().skip(1));
+
+            // try to merge with the next curve too
+            continue;
+        }
+
+        i += 1;
+    }
+}
+
+// Tries to find a single curve that passes through both curves.
+//
+// If both curves are the result of splitting a single curve at the point `t`,
+// then we can restore it by extending the outer control points.
+// In this case, the distances from the split point to the inner control points
+// are proportional to `t` and `1 - t`.
+fn fit_curve(curve1: &[Point; 4], curve2: &[Point; 4],
+             samples1: &[Point], samples2: &[Point], tolerance: f64) -> Option<Segment> {
+    let d1 = dist(curve1[2], curve1[3]);
+    let d2 = dist(curve2[0], curve2[1]);
+    if d1 == 0.0 || d2 == 0.0 {
+        return None;
+    }
+
+    let t = d1 / (d1 + d2);
+
+    let p0 = curve1[0];
+    let p3 = curve2[3];
+    let p1 = (p0.0 + (curve1[1].0 - p0.0) / t, p0.1 + (curve1[1].1 - p0.1) / t);
+    let p2 = (p3.0 + (curve2[2].0 - p3.0) / (1.0 - t), p3.1 + (curve2[2].1 - p3.1) / (1.0 - t));
+
+    let fitted = sample_curve(p0, p1, p2, p3, FIT_SAMPLES);
+
+    // the original curves should be close to the new one
+    let is_close = samples1.iter().chain(samples2)
+                           .all(|p| dist_to_polyline(*p, &fitted) <= tolerance);
+    if !is_close {
+        return None;
+    }
+
+    // and vice versa
+    let original: Vec<Point> = samples1.iter().chain(samples2.iter().skip(1)).cloned().collect();
+    if !fitted.iter().all(|p| dist_to_polyline(*p, &original) <= tolerance) {
+        return None;
+    }
+
+    Some(Segment::new_curve_to(p1.0, p1.1, p2.0, p2.1, p3.0, p3.1))
+}
+
+// Removes points from subsequent lines using the Ramer–Douglas–Peucker algorithm.
+fn simplify_lines(path: &mut Path, tolerance: f64) {
+    let points = end_points(path);
+    let mut keep = vec![true; path.d.len()];
+
+    let is_line = |seg: &Segment| {
+        match *seg.data() {
+            SegmentData::LineTo { .. } => true,
+            _ => false,
+        }
+    };
+
+    let mut i = 1;
+    while i < path.d.len() {
+        if !is_line(&path.d[i]) {
+            i += 1;
+            continue;
+        }
+
+        let start = i - 1;
+        while i < path.d.len() && is_line(&path.d[i]) {
+            i += 1;
+        }
+
+        // a polyline from the previous segment end point to the last line end point
+        rdp(&points[start..i], tolerance, &mut keep[start..i]);
+    }
+
+    let mut idx = 0;
+    path.d.retain(|_| {
+        idx += 1;
+        keep[idx - 1]
+    });
+}
+
+fn rdp(points: &[Point], tolerance: f64, keep: &mut [bool]) {
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        if last <= first + 1 {
+            continue;
+        }
+
+        let mut max_dist = 0.0;
+        let mut max_idx = first;
+        for k in (first + 1)..last {
+            let d = dist_to_segment(points[k], points[first], points[last]);
+            if d > max_dist {
+                max_dist = d;
+                max_idx = k;
+            }
+        }
+
+        if max_dist > tolerance {
+            stack.push((first, max_idx));
+            stack.push((max_idx, last));
+        } else {
+            for k in keep.iter_mut().take(last).skip(first + 1) {
+                *k = false;
+            }
+        }
+    }
+}
+
+fn reflect(p: Point, center: Point) -> Point {
+    (center.0 * 2.0 - p.0, center.1 * 2.0 - p.1)
+}
+
+fn sample_curve(p0: Point, p1: Point, p2: Point, p3: Point, count: usize) -> Vec<Point> {
+    (0..count + 1).map(|k| {
+        let t = k as f64 / count as f64;
+        let mt = 1.0 - t;
+        let a = mt * mt * mt;
+        let b = 3.0 * mt * mt * t;
+        let c = 3.0 * mt * t * t;
+        let d = t * t * t;
+        (a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+         a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1)
+    }).collect()
+}
+
+fn dist(p1: Point, p2: Point) -> f64 {
+    (p2.0 - p1.0).hypot(p2.1 - p1.1)
+}
+
+fn dist_to_segment(p: Point, a: Point, b: Point) -> f64 {
+    let dx = b.0 - a.0;
+    let dy = b.1 - a.1;
+    let len2 = dx * dx + dy * dy;
+    if len2 == 0.0 {
+        return dist(p, a);
+    }
+
+    let t = (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len2).max(0.0).min(1.0);
+    dist(p, (a.0 + t * dx, a.1 + t * dy))
+}
+
+fn dist_to_polyline(p: Point, points: &[Point]) -> f64 {
+    // a distance to the segment is never bigger than to its start point
+    let mut min = dist(p, points[0]);
+    for w in points.windows(2) {
+        min = min.min(dist_to_segment(p, w[0], w[1]));
+    }
+
+    min
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{FromStream};
+    use svgdom::types::path::Path;
+
+    macro_rules! test {
+        ($name:ident, $tolerance:expr, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let mut path = Path::from_data($in_text).unwrap();
+                path.conv_to_absolute();
+                simplify_path(&mut path, $tolerance);
+                assert_eq_text!(path.to_string(), $out_text);
+            }
+        )
+    }
+
+    test!(lines_1, 0.1, b"M 0 0 L 10 0.04 L 20 0 L 30 0.03 L 40 0",
+                        "M 0 0 L 40 0");
+
+    test!(lines_2, 0.1, b"M 0 0 L 10 0.05 L 20 10 L 30 20.02 L 40 30",
+                        "M 0 0 L 10 0.05 L 40 30");
+
+    test!(lines_3, 0.1, b"M 0 0 H 10 H 20 V 1 Z",
+                        "M 0 0 L 20 0 L 20 1 Z");
+
+    test!(curve_to_line_1, 0.1, b"M 0 0 C 10 0.01 20 0.01 30 0",
+                                "M 0 0 L 30 0");
+
+    // an S-shaped curve can't be represented by a single curve
+    test!(merge_curves_1, 0.1, b"M 0 0 C 5 10 15 10 20 0 C 25 -10 35 -10 40 0",
+                                 "M 0 0 C 5 10 15 10 20 0 C 25 -10 35 -10 40 0");
+
+    // a curve split at 't = 0.5'
+    test!(merge_curves_2, 0.1, b"M 0 0 C 0 5 2.5 10 7.5 15 C 12.5 20 20 25 30 30",
+                                 "M 0 0 C 0 10 10 20 30 30");
+
+    // zero tolerance disables the simplification
+    test!(keep_1, 0.0, b"M 0 0 L 10 0 L 20 0",
+                       "M 0 0 L 10 0 L 20 0");
+}
@@ -21,79 +21,131 @@
 ****************************************************************************/
 
 use super::short::{EId, AId};
+use super::scripting;
 
-use svgdom::{Document, AttributeValue, AttributeType};
+use svgdom::{Document, Node, AttributeValue, AttributeType};
 
-use error::Error;
+use error::{Error, ErrorElement, Diagnostic, Severity};
+use options::Options;
 
-pub fn preclean_checks(doc: &Document) -> Result<(), Error> {
-    check_for_unsupported_elements(doc)?;
-    check_for_script_attributes(doc)?;
-    check_for_conditional_attributes(doc)?;
-    check_for_external_xlink(doc)?;
+pub fn preclean_checks(doc: &Document, options: &Options) -> Result<(), Error> {
+    let list = collect_diagnostics(doc, options);
+    match list.into_iter().find(|d| d.is_blocking()) {
+        Some(d) => Err(d.error),
+        None => Ok(()),
+    }
+}
+
+/// Returns all problems in the document, and not only the first one.
+pub fn collect_diagnostics(doc: &Document, options: &Options) -> Vec<Diagnostic> {
+    let mut list = Vec::new();
+
+    check_for_unsupported_elements(doc, options, &mut list);
+    check_for_script_attributes(doc, options, &mut list);
+    check_for_conditional_attributes(doc, options, &mut list);
+    check_for_external_xlink(doc, options, &mut list);
+
+    list
+}
 
-    Ok(())
+/// Checks that a document with scripts doesn't have style sheets.
+///
+/// The parser applies style sheets and removes them along with the classes they use,
+/// but scripts can still select elements by this classes. Since the document doesn't store
+/// style sheets, they are searched in the source data.
+pub fn check_style_sheets(doc: &Document, data: &[u8], options: &Options) -> Option<Error> {
+    if !options.allow_scripts || !scripting::has_scripts(doc) {
+        return None;
+    }
+
+    ErrorElement::find(data, "style").map(Error::StyleSheetIsNotSupported)
+}
+
+fn push_error(list: &mut Vec<Diagnostic>, error: Error) {
+    list.push(Diagnostic {
+        severity: Severity::Error,
+        error: error,
+    });
 }
 
-fn check_for_unsupported_elements(doc: &Document) -> Result<(), Error> {
+fn check_for_unsupported_elements(doc: &Document, options: &Options,
+                                  list: &mut Vec<Diagnostic>) {
     for node in doc.descendants().svg() {
         match node.tag_id().unwrap() {
             EId::Script => {
-                return Err(Error::ScriptingIsNotSupported);
+                if options.allow_scripts {
+                    continue;
+                }
+
+                push_error(list, Error::ScriptingIsNotSupported(ErrorElement::from_node(&node)));
             }
               EId::Animate
             | EId::Set
             | EId::AnimateMotion
             | EId::AnimateColor
             | EId::AnimateTransform => {
-                return Err(Error::AnimationIsNotSupported);
+                if options.allow_animation {
+                    continue;
+                }
+
+                push_error(list, Error::AnimationIsNotSupported(ErrorElement::from_node(&node)));
             }
             _ => {}
         }
     }
-
-    Ok(())
 }
 
-fn check_for_script_attributes(doc: &Document) -> Result<(), Error> {
+fn check_for_script_attributes(doc: &Document, options: &Options,
+                               list: &mut Vec<Diagnostic>) {
+    if options.allow_scripts {
+        return;
+    }
+
     for node in doc.descendants().svg() {
-        let attrs = node.attributes();
-        for attr in attrs.iter() {
-            if attr.is_graphical_event() || attr.is_document_event() || attr.is_animation_event() {
-                return Err(Error::ScriptingIsNotSupported);
-            }
+        if scripting::has_event_attributes(&node) {
+            push_error(list, Error::ScriptingIsNotSupported(ErrorElement::from_node(&node)));
         }
     }
-
-    Ok(())
 }
 
-fn check_for_conditional_attributes(doc: &Document) -> Result<(), Error> {
-    // TODO: what to do with 'requiredExtensions'?
-
-    macro_rules! check_attr {
-        ($aid:expr, $node:expr) => (
-            let attrs = $node.attributes();
-            if let Some(av) = attrs.get_value($aid) {
-                // libsvgdom doesn't parse this attributes, so they must have String type
-                let s = av.as_string().unwrap();
-                if !s.is_empty() {
-                    // NOTE: we are only care about non-empty attributes
-                    return Err(Error::ConditionalProcessingIsNotSupported);
-                }
-            }
-        )
+fn check_for_conditional_attributes(doc: &Document, options: &Options,
+                                    list: &mut Vec<Diagnostic>) {
+    // all conditional attributes will be evaluated
+    if options.resolve_switch {
+        return;
     }
 
-    for node in doc.descendants().svg() {
-        check_attr!(AId::RequiredFeatures, node);
-        check_attr!(AId::SystemLanguage, node);
+    // NOTE: we are only care about non-empty attributes
+    fn is_set(node: &Node, aid: AId) -> bool {
+        let attrs = node.attributes();
+        match attrs.get_value(aid) {
+            // libsvgdom doesn't parse this attributes, so they must have String type
+            Some(av) => !av.as_string().unwrap().is_empty(),
+            None => false,
+        }
     }
 
-    Ok(())
+    for node in doc.descendants().svg() {
+        if is_set(&node, AId::RequiredFeatures) || is_set(&node, AId::SystemLanguage) {
+            push_error(list, Error::ConditionalProcessingIsNotSupported(
+                ErrorElement::from_node(&node)));
+        }
+
+        // 'requiredExtensions' will be processed like a regular attribute
+        if is_set(&node, AId::RequiredExtensions) {
+            list.push(Diagnostic {
+                severity: Severity::Warning,
+                error: Error::RequiredExtensionsIsNotSupported(ErrorElement::from_node(&node)),
+            });
+        }
+    }
 }
 
-fn check_for_external_xlink(doc: &Document) -> Result<(), Error> {
+fn check_for_external_xlink(doc: &Document, options: &Options, list: &mut Vec<Diagnostic>) {
+    if options.allow_external_links {
+        return;
+    }
+
     for node in doc.descendants().svg() {
         if !node.has_attribute(AId::XlinkHref) {
             continue;
@@ -109,25 +161,24 @@ fn check_for_external_xlink(doc: &Document) -> Result<(), Error> {
 
         let attrs = node.attributes();
         if let AttributeValue::String(ref s) = *attrs.get_value(AId::XlinkHref).unwrap() {
-            return Err(Error::ExternalHrefIsNotSupported(s.clone()));
+            push_error(list, Error::ExternalHrefIsNotSupported(s.clone(),
+                                                               ErrorElement::from_node(&node)));
         }
     }
-
-    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use svgdom::Document;
-    use error::Error;
 
     macro_rules! test {
         ($name:ident, $in_text:expr, $err:expr) => (
             #[test]
             fn $name() {
                 let doc = Document::from_data($in_text).unwrap();
-                assert_eq!(preclean_checks(&doc).err().unwrap(), $err);
+                let opt = Options::default();
+                assert_eq!(preclean_checks(&doc, &opt).err().unwrap().to_string(), $err);
             }
         )
     }
@@ -137,26 +188,78 @@ mod tests {
             #[test]
             fn $name() {
                 let doc = Document::from_data($in_text).unwrap();
-                assert_eq!(preclean_checks(&doc).is_ok(), true);
+                let opt = Options::default();
+                assert_eq!(preclean_checks(&doc, &opt).is_ok(), true);
             }
         )
     }
 
     test!(test_scripting_1, b"<svg><script/></svg>",
-          Error::ScriptingIsNotSupported);
+          "Scripting is not supported: <script>");
 
     test!(test_scripting_2, b"<svg onload=''/>",
-          Error::ScriptingIsNotSupported);
+          "Scripting is not supported: <svg>");
 
     test!(test_animation_1, b"<svg><set/></svg>",
-          Error::AnimationIsNotSupported);
+          "Animation is not supported: <set>");
 
     test!(test_conditions_1, b"<svg><switch requiredFeatures='text'/></svg>",
-          Error::ConditionalProcessingIsNotSupported);
+          "Conditional processing attributes is not supported: <switch>");
+
+    test!(test_conditions_2, b"<svg><switch id='s1' systemLanguage='en'/></svg>",
+          "Conditional processing attributes is not supported: <switch id=\"s1\">");
 
-    test!(test_conditions_2, b"<svg><switch systemLanguage='en'/></svg>",
-          Error::ConditionalProcessingIsNotSupported);
+    #[test]
+    fn collect_all_1() {
+        let doc = Document::from_data(b"<svg>
+            <script/>
+            <rect onclick='' requiredExtensions='http://example.org'/>
+            <set/>
+        </svg>").unwrap();
+
+        let list = collect_diagnostics(&doc, &Options::default());
+        let list: Vec<_> = list.iter().map(|d| d.to_string()).collect();
+        assert_eq!(list, vec![
+            "Error: Scripting is not supported: <script>.",
+            "Error: Animation is not supported: <set>.",
+            "Error: Scripting is not supported: <rect>.",
+            "Warning: The 'requiredExtensions' attribute is not supported \
+             and will be ignored: <rect>.",
+        ]);
+    }
 
     test_ok!(test_conditions_3, b"<svg><switch requiredFeatures=''/></svg>");
     test_ok!(test_conditions_4, b"<svg><switch systemLanguage=''/></svg>");
+
+    #[test]
+    fn test_scripting_3() {
+        let doc = Document::from_data(b"<svg onload=''><script/></svg>").unwrap();
+
+        let mut opt = Options::default();
+        opt.allow_scripts = true;
+        assert_eq!(preclean_checks(&doc, &opt).is_ok(), true);
+    }
+
+    #[test]
+    fn test_animation_2() {
+        let doc = Document::from_data(b"<svg><rect><set attributeName='x' to='5'/></rect></svg>")
+            .unwrap();
+
+        let mut opt = Options::default();
+        opt.allow_animation = true;
+        assert_eq!(preclean_checks(&doc, &opt).is_ok(), true);
+    }
+
+    test!(test_external_1, b"<svg><use xlink:href='sprite.svg#icon'/></svg>",
+          "The 'xlink:href' attribute is referencing an external object 'sprite.svg#icon', \
+           which is not supported: <use>");
+
+    #[test]
+    fn test_external_2() {
+        let doc = Document::from_data(b"<svg><use xlink:href='sprite.svg#icon'/></svg>").unwrap();
+
+        let mut opt = Options::default();
+        opt.allow_external_links = true;
+        assert_eq!(preclean_checks(&doc, &opt).is_ok(), true);
+    }
 }
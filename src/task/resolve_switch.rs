@@ -0,0 +1,257 @@
+/****************************************************************************
+**
+** svgcleaner could help you to clean up your SVG files
+** from unnecessary data.
+** Copyright (C) 2012-2017 Evgeniy Reizner
+**
+** This program is free software; you can redistribute it and/or modify
+** it under the terms of the GNU General Public License as published by
+** the Free Software Foundation; either version 2 of the License, or
+** (at your option) any later version.
+**
+** This program is distributed in the hope that it will be useful,
+** but WITHOUT ANY WARRANTY; without even the implied warranty of
+** MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+** GNU General Public License for more details.
+**
+** You should have received a copy of the GNU General Public License along
+** with this program; if not, write to the Free Software Foundation, Inc.,
+** 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+**
+****************************************************************************/
+
+
+use super::short::{EId, AId};
+use super::animation;
+
+use svgdom::{Document, Node, Attributes};
+
+use options::Options;
+
+/// Evaluates conditional processing attributes using `Options::switch_languages`
+/// and `Options::switch_features`.
+///
+/// Only the first valid child of the `switch` element is preserved. Other elements with
+/// invalid conditions are removed too, since they will not be rendered.
+/// Conditional attributes of the remaining elements are removed.
+pub fn resolve_switch(doc: &Document, options: &Options) {
+    // doc must contain 'svg' node, so we can safely unwrap
+    let svg = doc.svg_element().unwrap();
+    process(&svg, options);
+}
+
+fn process(parent: &Node, options: &Options) {
+    let is_switch = parent.is_tag_name(EId::Switch);
+
+    let children: Vec<Node> = parent.children().svg().filter(is_conditional).collect();
+    let mut keep: Vec<bool> = children.iter().map(|n| is_valid(n, options)).collect();
+
+    if is_switch {
+        // only the first valid child will be rendered
+        if let Some(idx) = keep.iter().position(|v| *v) {
+            for (i, k) in keep.iter_mut().enumerate() {
+                *k = i == idx;
+            }
+        }
+    }
+
+    let is_resolvable = if is_switch {
+        // we can't resolve 'switch' partially
+        children.iter().zip(&keep).all(|(n, k)| *k || !is_used_tree(n))
+    } else {
+        true
+    };
+
+    if is_resolvable {
+        for (node, keep) in children.iter().zip(keep) {
+            if keep {
+                remove_conditional_attributes(node);
+            } else if is_switch || !is_used_tree(node) {
+                // referenced elements outside the 'switch' are preserved as is
+                node.remove();
+            }
+        }
+    }
+
+    for child in parent.children().svg() {
+        if child.has_children() {
+            process(&child, options);
+        }
+    }
+}
+
+// Descriptive and animation elements are not affected by conditional processing.
+fn is_conditional(node: &Node) -> bool {
+    match node.tag_id().unwrap() {
+          EId::Title
+        | EId::Desc
+        | EId::Metadata => false,
+        _ => !animation::is_animation(node),
+    }
+}
+
+fn is_used_tree(node: &Node) -> bool {
+    node.is_used() || node.descendants().svg().any(|n| n.is_used())
+}
+
+fn is_valid(node: &Node, options: &Options) -> bool {
+    let attrs = node.attributes();
+
+    if let Some(value) = get_string(&attrs, AId::RequiredFeatures) {
+        if !is_list_supported(value, &options.switch_features) {
+            return false;
+        }
+    }
+
+    if let Some(value) = get_string(&attrs, AId::RequiredExtensions) {
+        if !is_list_supported(value, &options.switch_features) {
+            return false;
+        }
+    }
+
+    if let Some(value) = get_string(&attrs, AId::SystemLanguage) {
+        // an empty string is evaluated to 'false' too
+        if !value.split(',').any(|l| is_language_supported(l.trim(), &options.switch_languages)) {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn get_string(attrs: &Attributes, aid: AId) -> Option<&str> {
+    // libsvgdom doesn't parse this attributes, so they must have String type
+    attrs.get_value(aid).map(|v| v.as_string().unwrap().as_str())
+}
+
+// Checks a space-separated list of features or extensions.
+fn is_list_supported(value: &str, features: &[String]) -> bool {
+    // an empty list is evaluated to 'false'
+    if value.trim().is_empty() {
+        return false;
+    }
+
+    value.split_whitespace().all(|uri| {
+        // features can be set only by a name, like 'Shape'
+        // in 'http://www.w3.org/TR/SVG11/feature#Shape'
+        let name = uri.rsplit('#').next().unwrap_or(uri);
+        features.iter().any(|f| f == uri || f == name)
+    })
+}
+
+// A user language matches a tag when it's equal to the tag or to its prefix,
+// which is followed by '-'. Like 'en' and 'en-US'.
+fn is_language_supported(tag: &str, languages: &[String]) -> bool {
+    let tag = tag.as_bytes();
+    languages.iter().any(|lang| {
+        let lang = lang.as_bytes();
+
+        if tag.len() == lang.len() {
+            tag.eq_ignore_ascii_case(lang)
+        } else if tag.len() > lang.len() {
+            tag[lang.len()] == b'-' && tag[..lang.len()].eq_ignore_ascii_case(lang)
+        } else {
+            false
+        }
+    })
+}
+
+fn remove_conditional_attributes(node: &Node) {
+    let mut attrs = node.attributes_mut();
+    attrs.remove(AId::RequiredFeatures);
+    attrs.remove(AId::RequiredExtensions);
+    attrs.remove(AId::SystemLanguage);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, WriteToString};
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let doc = Document::from_data($in_text).unwrap();
+
+                let mut opt = Options::default();
+                opt.switch_languages = vec![String::from("en")];
+                opt.switch_features = vec![String::from("Shape")];
+
+                resolve_switch(&doc, &opt);
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    test!(resolve_1,
+b"<svg>
+    <switch>
+        <rect id='de' systemLanguage='de'/>
+        <rect id='en' systemLanguage='en-US, en-GB'/>
+        <rect id='default'/>
+    </switch>
+    <rect id='fr' systemLanguage='fr'/>
+</svg>",
+"<svg>
+    <switch>
+        <rect id='en'/>
+    </switch>
+</svg>
+");
+
+    test!(resolve_2,
+b"<svg>
+    <switch>
+        <title/>
+        <rect id='r1' requiredFeatures='http://www.w3.org/TR/SVG11/feature#Text'/>
+        <rect id='r2' requiredFeatures='http://www.w3.org/TR/SVG11/feature#Shape'/>
+    </switch>
+</svg>",
+"<svg>
+    <switch>
+        <title/>
+        <rect id='r2'/>
+    </switch>
+</svg>
+");
+
+    test!(resolve_3,
+b"<svg>
+    <switch>
+        <foreignObject requiredExtensions='http://www.w3.org/1999/xhtml'/>
+        <rect systemLanguage=''/>
+    </switch>
+</svg>",
+"<svg>
+    <switch/>
+</svg>
+");
+
+    // referenced elements can't be removed
+    test!(keep_used_1,
+b"<svg>
+    <switch>
+        <rect id='r1' systemLanguage='de'/>
+        <rect id='r2'/>
+    </switch>
+    <use xlink:href='#r1'/>
+</svg>",
+"<svg>
+    <switch>
+        <rect id='r1' systemLanguage='de'/>
+        <rect id='r2'/>
+    </switch>
+    <use xlink:href='#r1'/>
+</svg>
+");
+
+    #[test]
+    fn language_1() {
+        let langs = vec![String::from("en")];
+        assert!(is_language_supported("en", &langs));
+        assert!(is_language_supported("EN-us", &langs));
+        assert!(!is_language_supported("eng", &langs));
+        assert!(!is_language_supported("", &langs));
+    }
+}
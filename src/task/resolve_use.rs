@@ -21,12 +21,14 @@
 ****************************************************************************/
 
 use super::short::{EId, AId, Unit};
+use super::scripting;
 
 use svgdom::{Document, AttributeValue};
 use svgdom::types::{Transform, Length};
 
 pub fn resolve_use(doc: &Document) {
     let mut nodes = Vec::new();
+    let has_scripts = scripting::has_scripts(doc);
 
     for node in doc.descendants().filter(|n| n.is_tag_name(EId::Use)) {
         if let Some(value) = node.attribute_value(AId::XlinkHref) {
@@ -53,6 +55,14 @@ pub fn resolve_use(doc: &Document) {
                     continue;
                 }
 
+                // scripts can depend on the 'use' id, classes and the tree structure
+                if    has_scripts
+                   && (node.has_id() || node.has_attribute(AId::Class)
+                       || scripting::is_scripted(&node)
+                       || scripting::is_scripted(&link)) {
+                    continue;
+                }
+
                 nodes.push((node.clone(), link.clone()));
             }
         }
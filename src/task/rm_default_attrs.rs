@@ -21,12 +21,14 @@
 ****************************************************************************/
 
 use super::short::{EId, AId, Unit};
+use super::animation;
 
 use svgdom::{Document, Attribute, AttributeType, AttributeValue, ValueId};
 use svgdom::types::Length;
 
 pub fn remove_default_attributes(doc: &Document) {
     let mut rm_list = Vec::with_capacity(16);
+    let has_animation = animation::has_animation(doc);
 
     for node in doc.descendants().svg() {
         let tag_name = node.tag_id().unwrap();
@@ -35,6 +37,11 @@ pub fn remove_default_attributes(doc: &Document) {
             let attrs = node.attributes();
 
             for (aid, attr) in attrs.iter_svg() {
+                // an animation can use the current value as a base one
+                if has_animation && animation::is_inherited_attribute_animated(&node, aid) {
+                    continue;
+                }
+
                 if attr.is_presentation() {
                     if attr.check_is_default() {
                         if let Some(n) = node.parents().find(|n| n.has_attribute(aid)) {
@@ -247,6 +254,19 @@ b"<svg fill='#ff0000'>
 "<svg fill='#ff0000'>
     <rect fill='#000000'/>
 </svg>
+");
+
+    test!(keep_animated_1,
+b"<svg>
+    <rect fill='#000000'>
+        <set attributeName='fill' to='#ff0000'/>
+    </rect>
+</svg>",
+"<svg>
+    <rect fill='#000000'>
+        <set attributeName='fill' to='#ff0000'/>
+    </rect>
+</svg>
 ");
 
     test!(rm_svg_w_h,
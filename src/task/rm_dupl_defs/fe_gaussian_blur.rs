@@ -21,10 +21,16 @@
 ****************************************************************************/
 
 use task::short::{EId, AId};
+use task::scripting;
 
 use svgdom::{Document, Node};
 
 pub fn remove_dupl_fe_gaussian_blur(doc: &Document) {
+    // scripts can reference removed elements by id
+    if scripting::has_scripts(doc) {
+        return;
+    }
+
     let filter_attrs = [
         AId::X,
         AId::Y,
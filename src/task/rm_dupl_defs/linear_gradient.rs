@@ -21,10 +21,16 @@
 ****************************************************************************/
 
 use task::short::{EId, AId};
+use task::scripting;
 
 use svgdom::{Document, Node};
 
 pub fn remove_dupl_linear_gradients(doc: &Document) {
+    // scripts can reference removed elements by id
+    if scripting::has_scripts(doc) {
+        return;
+    }
+
     let attrs = [
         AId::X1,
         AId::Y1,
@@ -187,5 +193,26 @@ b"<svg>
 "<svg>
     <linearGradient id='lg1'/>
 </svg>
+");
+
+    test!(keep_external_1,
+b"<svg>
+    <linearGradient id='lg1' xlink:href='gradients.svg#lg1'>
+        <stop/>
+    </linearGradient>
+    <linearGradient id='lg2'>
+        <stop/>
+    </linearGradient>
+    <rect fill='url(#lg2)'/>
+</svg>",
+"<svg>
+    <linearGradient id='lg1' xlink:href='gradients.svg#lg1'>
+        <stop/>
+    </linearGradient>
+    <linearGradient id='lg2'>
+        <stop/>
+    </linearGradient>
+    <rect fill='url(#lg2)'/>
+</svg>
 ");
 }
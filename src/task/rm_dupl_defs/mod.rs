@@ -29,6 +29,7 @@ mod radial_gradient;
 mod fe_gaussian_blur;
 
 use task::short::AId;
+use task::utils;
 use svgdom::types::{Transform};
 use svgdom::{Node, AttributeValue};
 
@@ -57,6 +58,11 @@ fn rm_loop<F>(nodes: &mut Vec<Node>, cmp: F)
             let node2 = nodes[i2].clone();
             i2 += 1;
 
+            // elements with external links should not be removed
+            if utils::has_external_link(&node1) || utils::has_external_link(&node2) {
+                continue;
+            }
+
             if !cmp(&node1, &node2) {
                 continue;
             }
@@ -21,10 +21,16 @@
 ****************************************************************************/
 
 use task::short::{EId, AId};
+use task::scripting;
 
 use svgdom::{Document, Node};
 
 pub fn remove_dupl_radial_gradients(doc: &Document) {
+    // scripts can reference removed elements by id
+    if scripting::has_scripts(doc) {
+        return;
+    }
+
     let attrs = [
         AId::Cx,
         AId::Cy,
@@ -21,6 +21,8 @@
 ****************************************************************************/
 
 use super::short::{EId, AId};
+use super::animation;
+use super::scripting;
 
 use svgdom::{Document, Node, ElementType, AttributeValue, ValueId};
 use svgdom::types::FuzzyEq;
@@ -97,8 +99,11 @@ fn is_valid_clip_path_elem(node: &Node) -> bool {
 
     if node.is_tag_name(EId::Use) {
         if let Some(av) = node.attribute_value(AId::XlinkHref) {
-            if let AttributeValue::Link(link) = av {
-                return is_valid_shape(&link);
+            match av {
+                AttributeValue::Link(link) => return is_valid_shape(&link),
+                // we don't know what an external element is, so we should keep it
+                AttributeValue::String(_) => return true,
+                _ => {}
             }
         }
     }
@@ -135,8 +140,9 @@ fn process_paths(doc: &Document, is_any_removed: &mut bool) {
 // Remove elements with 'display:none'.
 fn process_display_attribute(doc: &Document, is_any_removed: &mut bool) {
     let mut nodes = Vec::with_capacity(16);
+    let has_scripts = scripting::has_scripts(doc);
 
-    _process_display_attribute(&doc.root(), &mut nodes, is_any_removed);
+    _process_display_attribute(&doc.root(), has_scripts, &mut nodes, is_any_removed);
 
     if !nodes.is_empty() {
         *is_any_removed = true;
@@ -147,17 +153,22 @@ fn process_display_attribute(doc: &Document, is_any_removed: &mut bool) {
     }
 }
 
-fn _process_display_attribute(parent: &Node, nodes: &mut Vec<Node>, is_any_removed: &mut bool) {
+fn _process_display_attribute(parent: &Node, has_scripts: bool, nodes: &mut Vec<Node>,
+                              is_any_removed: &mut bool) {
     for node in parent.children().svg() {
         // if elements has attribute 'display:none' and this element is not used - we can remove it
-        if node.has_attribute_with_value(AId::Display, ValueId::None) && !node.is_used() {
+        // an element can be shown by an animation or a script
+        if    node.has_attribute_with_value(AId::Display, ValueId::None)
+           && !node.is_used()
+           && !animation::is_attribute_animated(&node, AId::Display)
+           && !(has_scripts && node.descendants().svg().any(|n| n.has_id())) {
             // all children must be unused to
             if !node.descendants().svg().any(|n| n.is_used()) {
                 // TODO: ungroup used elements and remove unused
                 nodes.push(node.clone());
             }
         } else if node.has_children() {
-            _process_display_attribute(&node, nodes, is_any_removed);
+            _process_display_attribute(&node, has_scripts, nodes, is_any_removed);
         }
     }
 }
@@ -391,6 +402,16 @@ b"<svg>
     <rect id='rect1' height='5' width='5'/>
     <g id='g1'/>
 </svg>
+");
+
+    test_eq!(keep_clip_path_children_1,
+b"<svg>
+    <defs>
+        <clipPath>
+            <use xlink:href='shapes.svg#rect1'/>
+        </clipPath>
+    </defs>
+</svg>
 ");
 
     test!(rm_clip_path_1,
@@ -465,6 +486,15 @@ b"<svg>
     </g>
     <use xlink:href='#r1'/>
 </svg>
+");
+
+    test_eq!(skip_display_none_2,
+b"<svg>
+    <g display='none'>
+        <rect height='5' width='5'/>
+        <set attributeName='display' to='inline' begin='1s'/>
+    </g>
+</svg>
 ");
 
     test!(rm_filter_1,
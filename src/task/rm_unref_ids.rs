@@ -22,9 +22,20 @@
 
 use svgdom::Document;
 
+use super::animation;
+use super::scripting;
+
 pub fn remove_unreferenced_ids(doc: &Document) {
+    // scripts can reference any id
+    if scripting::has_scripts(doc) {
+        return;
+    }
+
+    // ids from the animation timing attributes are not links
+    let keep_ids = animation::collect_timing_ids(doc);
+
     for node in doc.descendants().svg() {
-        if node.has_id() && !node.is_used() {
+        if node.has_id() && !node.is_used() && !keep_ids.contains(&*node.id()) {
             node.set_id(String::new());
         }
     }
@@ -53,5 +64,20 @@ b"<svg>
     <radialGradient/>
     <rect fill='url(#lg1)'/>
 </svg>
+");
+
+    test!(keep_timing_1,
+b"<svg>
+    <rect id='r1'>
+        <set id='s1' attributeName='x' to='5' begin='r1.click'/>
+        <set attributeName='y' to='5' begin='s1.end'/>
+    </rect>
+</svg>",
+"<svg>
+    <rect id='r1'>
+        <set id='s1' attributeName='x' to='5' begin='r1.click'/>
+        <set attributeName='y' to='5' begin='s1.end'/>
+    </rect>
+</svg>
 ");
 }
@@ -61,12 +61,11 @@ pub fn remove_unused_coordinates(doc: &Document) {
                     // in resolve_attrs::radial_gradients
 
                     // process only 'radialGradient' which is not linked
-                    // to the other 'radialGradient'
-                    if attrs.contains(AId::XlinkHref) {
-                        if let Some(v) = attrs.get_value(AId::XlinkHref) {
-                            if v.as_link().unwrap().is_tag_name(EId::RadialGradient) {
-                                continue;
-                            }
+                    // to the other 'radialGradient' or to an external one
+                    if let Some(v) = attrs.get_value(AId::XlinkHref) {
+                        match v.as_link() {
+                            Some(link) if !link.is_tag_name(EId::RadialGradient) => {}
+                            _ => continue,
                         }
                     }
 
@@ -21,6 +21,8 @@
 ****************************************************************************/
 
 use super::short::EId;
+use super::scripting;
+use super::utils;
 
 use svgdom::{Document, Node};
 
@@ -28,18 +30,30 @@ pub fn remove_unused_defs(doc: &Document) {
     // unwrap is safe, because 'defs' already had been created in 'group_defs'
     let defs = doc.descendants().filter(|n| n.is_tag_name(EId::Defs)).nth(0).unwrap();
 
+    let has_scripts = scripting::has_scripts(doc);
+
     // repeat until no unused nodes left
-    while remove_unused_defs_impl(&defs) { }
+    while remove_unused_defs_impl(&defs, has_scripts) { }
 }
 
 // Returns true if tree structure has been changed.
-fn remove_unused_defs_impl(defs: &Node) -> bool {
+fn remove_unused_defs_impl(defs: &Node, has_scripts: bool) -> bool {
     // TODO: understand how styles are propagates inside defs
 
     let mut mv_nodes = Vec::new();
     let mut rm_nodes = Vec::new();
 
     for node in defs.children() {
+        // scripts can reference any element by id
+        if has_scripts && (node.has_id() || node.is_tag_name(EId::Script)) {
+            continue;
+        }
+
+        // external links are opaque
+        if utils::has_external_link(&node) {
+            continue;
+        }
+
         if !node.is_used() && !is_font_node(&node) {
             // nodes outside defs we have to move to main 'defs' node, not to parent,
             // because otherwise they became renderable
@@ -259,5 +273,13 @@ b"<svg>
         </font-face>
     </defs>
 </svg>
+");
+
+    test_eq!(keep_external_1,
+b"<svg>
+    <defs>
+        <linearGradient xlink:href='gradients.svg#lg1'/>
+    </defs>
+</svg>
 ");
 }
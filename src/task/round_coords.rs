@@ -0,0 +1,446 @@
+/****************************************************************************
+**
+** svgcleaner could help you to clean up your SVG files
+** from unnecessary data.
+** Copyright (C) 2012-2017 Evgeniy Reizner
+**
+** This program is free software; you can redistribute it and/or modify
+** it under the terms of the GNU General Public License as published by
+** the Free Software Foundation; either version 2 of the License, or
+** (at your option) any later version.
+**
+** This program is distributed in the hope that it will be useful,
+** but WITHOUT ANY WARRANTY; without even the implied warranty of
+** MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+** GNU General Public License for more details.
+**
+** You should have received a copy of the GNU General Public License along
+** with this program; if not, write to the Free Software Foundation, Inc.,
+** 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+**
+****************************************************************************/
+
+
+use std::f64::consts::SQRT_2;
+
+use svgdom::{Document, Node, AttributeValue, WriteOptions};
+use svgdom::types::Transform;
+use svgdom::types::path::{Path, Segment, SegmentData};
+
+use task::short::{EId, AId, Unit};
+use task::animation;
+use task::paths;
+use task::utils::{self, singular_values};
+use options::{Options, Tolerance};
+
+// The maximum precision that makes sense for f64.
+const MAX_PRECISION: i32 = 12;
+
+/// Rounds coordinates of each element to the minimal amount of fractional digits
+/// that keeps the rendering error under `auto_precision_error`.
+///
+/// The amount of digits depends on the root element viewport and on the transforms
+/// of each element and its parents, so coordinates of a scaled element will be more precise.
+///
+/// Segments of the paths processed by `process_paths` are converted again,
+/// because the shortest form of a segment depends on the precision.
+pub fn round_coordinates(doc: &Document, options: &Options, write_opt: &WriteOptions) {
+    let svg = doc.svg_element().unwrap();
+
+    let (px_scale, viewport) = match utils::viewport_size(doc) {
+        Some((w, h, px_scale)) => (px_scale, w.max(h)),
+        None => return,
+    };
+
+    // the maximum error in pixels
+    let max_error = match options.auto_precision_error {
+        Tolerance::UserUnits(v) => v,
+        Tolerance::Viewport(v) => viewport * px_scale * v / 100.0,
+    };
+
+    if max_error <= 0.0 {
+        return;
+    }
+
+    // The error budget is split into thirds: one for the shapes coordinates,
+    // one for the translate parts of transforms and one for the rest of the matrices.
+    // Nested transforms add up, so each nesting level gets a half of what is left.
+    let ctx = Context {
+        px_scale: px_scale,
+        viewport: viewport,
+        part_error: max_error / 3.0,
+        has_animation: animation::has_animation(doc),
+        options: options,
+        write_opt: write_opt,
+    };
+
+    process(&svg, Transform::default(), 0, &ctx);
+}
+
+struct Context<'a> {
+    // the amount of pixels in a root element user unit
+    px_scale: f64,
+    // the largest side of the root element viewport in user units
+    viewport: f64,
+    // a third of the maximum error in pixels
+    part_error: f64,
+    has_animation: bool,
+    options: &'a Options,
+    write_opt: &'a WriteOptions,
+}
+
+impl<'a> Context<'a> {
+    // Returns the amount of fractional digits needed for a value that will be scaled by `scale`,
+    // so the error will not exceed `max_error` pixels.
+    fn precision(&self, scale: f64, max_error: f64) -> i32 {
+        // a rounding error is not bigger than a half of the last digit
+        let digits = (scale * self.px_scale / (2.0 * max_error)).log10().ceil();
+
+        if digits.is_nan() {
+            MAX_PRECISION
+        } else {
+            (digits as i32).max(0).min(MAX_PRECISION)
+        }
+    }
+}
+
+// `depth` is the amount of transforms applied by the parents.
+fn process(parent: &Node, parent_ts: Transform, depth: i32, ctx: &Context) {
+    for node in parent.children().svg() {
+        // Referenced elements can be rendered in a different coordinate system,
+        // or even use 'objectBoundingBox' units, so we can't process them.
+        if node.is_used() {
+            continue;
+        }
+
+        let is_container = match node.tag_id().unwrap() {
+            EId::G | EId::A | EId::Switch => true,
+              EId::Path
+            | EId::Rect
+            | EId::Circle
+            | EId::Ellipse
+            | EId::Line
+            | EId::Polyline
+            | EId::Polygon => false,
+            _ => continue,
+        };
+
+        if ctx.has_animation && animation::is_animated(&node) {
+            continue;
+        }
+
+        let mut ts = parent_ts;
+        let mut depth = depth;
+        let node_ts = match node.attributes().get_value(AId::Transform) {
+            Some(&AttributeValue::Transform(node_ts)) => Some(node_ts),
+            _ => None,
+        };
+
+        if let Some(node_ts) = node_ts {
+            ts.append(&node_ts);
+            depth += 1;
+            round_transform(&node, &parent_ts, &ts, depth, ctx);
+        }
+
+        if is_container {
+            process(&node, ts, depth, ctx);
+        } else {
+            // a point can be moved by a half of the last digit along each axis
+            let (scale, _) = singular_values(&ts);
+            round_shape(&node, ctx.precision(scale * SQRT_2, ctx.part_error), ctx);
+        }
+    }
+}
+
+// `abs_ts` is the parent transform with the node transform applied.
+fn round_transform(node: &Node, parent_ts: &Transform, abs_ts: &Transform, depth: i32,
+                   ctx: &Context) {
+    let (parent_scale, _) = singular_values(parent_ts);
+    let (_, min_scale) = singular_values(abs_ts);
+
+    // a degenerate transform
+    if min_scale == 0.0 {
+        return;
+    }
+
+    let max_error = ctx.part_error / 2f64.powi(depth);
+
+    // The largest node coordinate of a visible point: a viewport corner,
+    // moved back by the translate and scaled down by the transform.
+    let extent = (ctx.viewport * SQRT_2 + abs_ts.e.hypot(abs_ts.f)) / min_scale;
+
+    let mut attrs = node.attributes_mut();
+    let ts = match attrs.get_value_mut(AId::Transform) {
+        Some(&mut AttributeValue::Transform(ref mut ts)) => ts,
+        _ => return,
+    };
+
+    // Translate is in parent units.
+    let prec = ctx.precision(parent_scale * SQRT_2, max_error);
+    ts.e = round(ts.e, prec);
+    ts.f = round(ts.f, prec);
+
+    // An error of the other values is multiplied by the node coordinates
+    // and then by the parent transform.
+    let prec = ctx.precision(parent_scale * 2.0 * extent, max_error);
+    ts.a = round(ts.a, prec);
+    ts.b = round(ts.b, prec);
+    ts.c = round(ts.c, prec);
+    ts.d = round(ts.d, prec);
+}
+
+fn round_shape(node: &Node, prec: i32, ctx: &Context) {
+    let mut attrs = node.attributes_mut();
+
+    for aid in &[AId::X, AId::Y, AId::Width, AId::Height, AId::Cx, AId::Cy, AId::R,
+                 AId::Rx, AId::Ry, AId::X1, AId::Y1, AId::X2, AId::Y2] {
+        if let Some(&mut AttributeValue::Length(ref mut len)) = attrs.get_value_mut(*aid) {
+            if len.unit == Unit::None {
+                len.num = round(len.num, prec);
+            }
+        }
+    }
+
+    if let Some(&mut AttributeValue::NumberList(ref mut list)) = attrs.get_value_mut(AId::Points) {
+        for n in list.iter_mut() {
+            *n = round(*n, prec);
+        }
+    }
+
+    if let Some(&mut AttributeValue::Path(ref mut path)) = attrs.get_value_mut(AId::D) {
+        round_path(path, prec, ctx);
+    }
+}
+
+fn round_path(path: &mut Path, prec: i32, ctx: &Context) {
+    // Relative coordinates should not be rounded directly, because an error will be
+    // accumulated. So we round absolute coordinates and then derive relative segments
+    // from them.
+    let is_absolute: Vec<bool> = path.d.iter().map(|seg| seg.absolute).collect();
+
+    path.conv_to_absolute();
+    for seg in path.d.iter_mut() {
+        *seg = round_segment(seg, prec);
+    }
+
+    if ctx.options.paths_to_relative {
+        // the paths processing has chosen segments for the original coordinates
+        paths::reprocess_path(path, ctx.options, ctx.write_opt);
+    } else {
+        // keep the form of each segment
+        if is_absolute.iter().all(|v| *v) {
+            return;
+        }
+
+        let mut rel = path.clone();
+        rel.conv_to_relative();
+
+        for ((seg, rel_seg), is_abs) in path.d.iter_mut().zip(rel.d.iter()).zip(is_absolute) {
+            if !is_abs {
+                *seg = *rel_seg;
+            }
+        }
+    }
+
+    // a difference of two rounded numbers can contain a floating point noise
+    for seg in path.d.iter_mut().filter(|seg| !seg.absolute) {
+        *seg = round_segment(seg, prec);
+    }
+}
+
+fn round_segment(seg: &Segment, prec: i32) -> Segment {
+    let r = |n| round(n, prec);
+    let mut new_seg = match *seg.data() {
+        SegmentData::MoveTo { x, y } => Segment::new_move_to(r(x), r(y)),
+        SegmentData::LineTo { x, y } => Segment::new_line_to(r(x), r(y)),
+        SegmentData::HorizontalLineTo { x } => Segment::new_hline_to(r(x)),
+        SegmentData::VerticalLineTo { y } => Segment::new_vline_to(r(y)),
+        SegmentData::CurveTo { x1, y1, x2, y2, x, y } => {
+            Segment::new_curve_to(r(x1), r(y1), r(x2), r(y2), r(x), r(y))
+        }
+        SegmentData::SmoothCurveTo { x2, y2, x, y } => {
+            Segment::new_smooth_curve_to(r(x2), r(y2), r(x), r(y))
+        }
+        SegmentData::Quadratic { x1, y1, x, y } => {
+            Segment::new_quad_to(r(x1), r(y1), r(x), r(y))
+        }
+        SegmentData::SmoothQuadratic { x, y } => Segment::new_smooth_quad_to(r(x), r(y)),
+        SegmentData::EllipticalArc { rx, ry, x_axis_rotation, large_arc, sweep, x, y } => {
+            // rotation is in degrees, so we keep it as is
+            Segment::new_arc_to(r(rx), r(ry), x_axis_rotation, large_arc, sweep, r(x), r(y))
+        }
+        SegmentData::ClosePath => Segment::new_close_path(),
+    };
+
+    new_seg.absolute = seg.absolute;
+    new_seg
+}
+
+fn round(n: f64, prec: i32) -> f64 {
+    let p = 10f64.powi(prec);
+    (n * p).round() / p
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::{Document, WriteToString};
+    use options::Tolerance;
+
+    macro_rules! test {
+        ($name:ident, $in_text:expr, $out_text:expr) => (
+            #[test]
+            fn $name() {
+                let doc = Document::from_data($in_text).unwrap();
+                let mut opt = Options::default();
+                opt.auto_precision_error = Tolerance::UserUnits(0.1);
+                // keep segments as is
+                opt.paths_to_relative = false;
+                round_coordinates(&doc, &opt, &write_opt_for_tests!());
+                assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()), $out_text);
+            }
+        )
+    }
+
+    macro_rules! test_eq {
+        ($name:ident, $in_text:expr) => (
+            test!($name, $in_text, String::from_utf8_lossy($in_text));
+        )
+    }
+
+    test!(round_1,
+b"<svg viewBox='0 0 16 16'>
+    <rect height='5.123456' width='5.456789' x='1.06' y='1.001'/>
+</svg>",
+"<svg viewBox='0 0 16 16'>
+    <rect height='5.12' width='5.46' x='1.06' y='1'/>
+</svg>
+");
+
+    // a scaled element requires more digits
+    test!(round_2,
+b"<svg viewBox='0 0 16 16'>
+    <g transform='scale(100)'>
+        <circle cx='0.123456' cy='0.123456' r='0.05'/>
+    </g>
+</svg>",
+"<svg viewBox='0 0 16 16'>
+    <g transform='scale(100)'>
+        <circle cx='0.1235' cy='0.1235' r='0.05'/>
+    </g>
+</svg>
+");
+
+    // the viewport size in pixels matters too
+    test!(round_3,
+b"<svg height='1600' viewBox='0 0 16 16' width='1600'>
+    <path d='M 1.123456 1.123456 L 2.123456 2.123456'/>
+</svg>",
+"<svg height='1600' viewBox='0 0 16 16' width='1600'>
+    <path d='M 1.1235 1.1235 L 2.1235 2.1235'/>
+</svg>
+");
+
+    // a user unit size is unknown, so the 'viewBox' is used as is
+    test!(round_4,
+b"<svg height='10mm' viewBox='0 0 16 16' width='10mm'>
+    <path d='M 1.123456 1.123456 L 2.123456 2.123456'/>
+</svg>",
+"<svg height='10mm' viewBox='0 0 16 16' width='10mm'>
+    <path d='M 1.12 1.12 L 2.12 2.12'/>
+</svg>
+");
+
+    // relative segments are derived from the rounded absolute ones,
+    // so an error is not accumulated
+    test!(round_rel_1,
+b"<svg viewBox='0 0 16 16'>
+    <path d='M 1.114 1.114 l 1.114 1.114 L 3.342 3.342 l 1.114 1.114'/>
+</svg>",
+"<svg viewBox='0 0 16 16'>
+    <path d='M 1.11 1.11 l 1.12 1.12 L 3.34 3.34 l 1.12 1.12'/>
+</svg>
+");
+
+    // '3.0000004' and the relative '1.8765444' have the same length,
+    // but after rounding the absolute segment is shorter
+    #[test]
+    fn round_rel_2() {
+        let doc = Document::from_data(
+b"<svg viewBox='0 0 16 16'>
+    <path d='M 1.123456 1.123456 L 3.0000004 3.0000004'/>
+</svg>").unwrap();
+
+        let mut opt = Options::default();
+        opt.auto_precision_error = Tolerance::UserUnits(0.1);
+        let write_opt = write_opt_for_tests!();
+        paths::process_paths(&doc, &opt, &write_opt);
+        round_coordinates(&doc, &opt, &write_opt);
+
+        assert_eq_text!(doc.to_string_with_opt(&write_opt),
+"<svg viewBox='0 0 16 16'>
+    <path d='m 1.12 1.12 L 3 3'/>
+</svg>
+");
+    }
+
+    test_eq!(keep_used_1,
+b"<svg viewBox='0 0 16 16'>
+    <rect id='r1' height='5.123456' width='5'/>
+    <use xlink:href='#r1'/>
+</svg>
+");
+
+    // Returns the path points in the root element coordinates.
+    fn root_points(doc: &Document) -> Vec<(f64, f64)> {
+        let mut points = Vec::new();
+        for node in doc.descendants().svg().filter(|n| n.is_tag_name(EId::Path)) {
+            let ts = utils::abs_transform(&node);
+
+            let mut path = match node.attribute_value(AId::D) {
+                Some(AttributeValue::Path(path)) => path,
+                _ => continue,
+            };
+            path.conv_to_absolute();
+
+            for seg in &path.d {
+                match *seg.data() {
+                      SegmentData::MoveTo { x, y }
+                    | SegmentData::LineTo { x, y } => points.push(ts.apply(x, y)),
+                    _ => {}
+                }
+            }
+        }
+
+        points
+    }
+
+    // errors of the nested transforms and of the coordinates add up,
+    // but should stay within the limit
+    #[test]
+    fn nested_error_1() {
+        let doc = Document::from_data(
+b"<svg viewBox='0 0 100 100'>
+    <g transform='translate(10.123456 20.654321) scale(3.3333333)'>
+        <g transform='matrix(0.7071068 0.7071068 -0.7071068 0.7071068 5.5555555 -3.3333333)'>
+            <g transform='scale(1.2345678)'>
+                <path d='M 1.234567 2.345678 L 10.987654 3.456789 L 5.555555 8.888888 Z'/>
+            </g>
+        </g>
+    </g>
+</svg>").unwrap();
+
+        let orig = root_points(&doc);
+
+        let mut opt = Options::default();
+        opt.auto_precision_error = Tolerance::UserUnits(0.1);
+        round_coordinates(&doc, &opt, &write_opt_for_tests!());
+
+        let new = root_points(&doc);
+        assert!(orig != new);
+        assert_eq!(orig.len(), new.len());
+        for (a, b) in orig.iter().zip(new.iter()) {
+            assert!((a.0 - b.0).hypot(a.1 - b.1) <= 0.1, "{:?} {:?}", a, b);
+        }
+    }
+}
@@ -0,0 +1,79 @@
+/****************************************************************************
+**
+** svgcleaner could help you to clean up your SVG files
+** from unnecessary data.
+** Copyright (C) 2012-2017 Evgeniy Reizner
+**
+** This program is free software; you can redistribute it and/or modify
+** it under the terms of the GNU General Public License as published by
+** the Free Software Foundation; either version 2 of the License, or
+** (at your option) any later version.
+**
+** This program is distributed in the hope that it will be useful,
+** but WITHOUT ANY WARRANTY; without even the implied warranty of
+** MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+** GNU General Public License for more details.
+**
+** You should have received a copy of the GNU General Public License along
+** with this program; if not, write to the Free Software Foundation, Inc.,
+** 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+**
+****************************************************************************/
+
+
+//! Helpers for documents with scripts.
+//!
+//! Scripts are rejected by `preclean_checks` unless `Options::allow_scripts` is set,
+//! so for other documents this methods are no-op.
+//!
+//! A script can reference any element by id or class and depend on the tree structure,
+//! so in documents with scripts all ids and classes are preserved and elements with event
+//! attributes, along with their parents, are not moved.
+
+use super::short::EId;
+
+use svgdom::{Document, Node};
+
+/// Checks that node has any event attributes, like `onclick`.
+pub fn has_event_attributes(node: &Node) -> bool {
+    node.attributes().iter().any(|attr| {
+        attr.is_graphical_event() || attr.is_document_event() || attr.is_animation_event()
+    })
+}
+
+/// Checks that document has any `script` elements or event attributes.
+pub fn has_scripts(doc: &Document) -> bool {
+    doc.descendants().svg().any(|n| n.is_tag_name(EId::Script) || has_event_attributes(&n))
+}
+
+/// Checks that node or any of its children has event attributes.
+///
+/// Such elements should not be moved or removed.
+pub fn is_scripted(node: &Node) -> bool {
+    has_event_attributes(node) || node.descendants().svg().any(|n| has_event_attributes(&n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgdom::Document;
+
+    #[test]
+    fn is_scripted_1() {
+        let doc = Document::from_data(b"<svg>
+            <g id='g1'>
+                <rect onclick='alert(1)'/>
+            </g>
+            <g id='g2'>
+                <rect/>
+            </g>
+        </svg>").unwrap();
+
+        let g1 = doc.descendants().find(|n| *n.id() == "g1").unwrap();
+        let g2 = doc.descendants().find(|n| *n.id() == "g2").unwrap();
+
+        assert!(has_scripts(&doc));
+        assert!(is_scripted(&g1));
+        assert!(!is_scripted(&g2));
+    }
+}
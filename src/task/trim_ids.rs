@@ -22,6 +22,9 @@
 
 use svgdom::Document;
 
+use super::animation;
+use super::scripting;
+
 const NUM_SIZE: usize = 5;
 const CHARS_SIZE: usize = 62;
 const MAX_NUM_CHAR: u8 = (CHARS_SIZE - 1) as u8;
@@ -107,9 +110,22 @@ impl Num {
 }
 
 pub fn trim_ids(doc: &Document) {
+    // scripts can reference any id
+    if scripting::has_scripts(doc) {
+        return;
+    }
+
+    // ids from the animation timing attributes are not links, so we can't rename them
+    let keep_ids = animation::collect_timing_ids(doc);
+
     let mut num = Num::new();
     for node in doc.descendants().svg() {
-        if node.has_id() {
+        if node.has_id() && !keep_ids.contains(&*node.id()) {
+            // new id should not be equal to the preserved one
+            while keep_ids.contains(&num.to_string()) {
+                num.plus_one();
+            }
+
             node.set_id(num.to_string());
             num.plus_one();
         }
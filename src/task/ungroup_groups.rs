@@ -21,6 +21,8 @@
 ****************************************************************************/
 
 use super::short::{EId, AId};
+use super::animation;
+use super::scripting;
 
 use svgdom::{Document, Node, AttributeValue};
 
@@ -29,8 +31,10 @@ pub fn ungroup_groups(doc: &Document) {
 
     // doc must contain 'svg' node, so we can safely unwrap
     let svg = doc.svg_element().unwrap();
+    let has_animation = animation::has_animation(doc);
+    let has_scripts = scripting::has_scripts(doc);
     loop {
-        _ungroup_groups(&svg, &mut groups);
+        _ungroup_groups(&svg, has_animation, has_scripts, &mut groups);
 
         if groups.is_empty() {
             break;
@@ -45,7 +49,8 @@ pub fn ungroup_groups(doc: &Document) {
 
 // Fill 'groups' vec with 'g' elements that should be removed.
 // This method is recursive.
-fn _ungroup_groups(root: &Node, groups: &mut Vec<Node>) {
+fn _ungroup_groups(root: &Node, has_animation: bool, has_scripts: bool,
+                   groups: &mut Vec<Node>) {
     // We can't ungoup groups if they have one of the listed attribute.
     // Checkout 'painting-marker-02-f.svg' in 'W3C_SVG_11_TestSuite' for details.
     let invalid_attrs = [AId::Mask, AId::ClipPath, AId::Filter];
@@ -55,6 +60,15 @@ fn _ungroup_groups(root: &Node, groups: &mut Vec<Node>) {
 
     for node in root.children() {
         if node.is_tag_name(EId::G) {
+            // Scripts can depend on the group id, classes and the tree structure,
+            // even if the group is empty.
+            if    has_scripts
+               && (node.has_id() || node.has_attribute(AId::Class)
+                   || scripting::is_scripted(&node)) {
+                _ungroup_groups(&node, has_animation, has_scripts, groups);
+                continue;
+            }
+
             if !node.has_children() && !node.has_attribute(AId::Filter) {
                 // Empty group without filter attribute.
                 // Checkout 'filters-tile-01-b.svg' in 'W3C_SVG_11_TestSuite' for details.
@@ -67,6 +81,13 @@ fn _ungroup_groups(root: &Node, groups: &mut Vec<Node>) {
                 continue;
             }
 
+            // Animations of the group or its children will be applied
+            // to other elements after ungrouping.
+            if has_animation && is_animated_group(&node) {
+                _ungroup_groups(&node, has_animation, has_scripts, groups);
+                continue;
+            }
+
             // group shouldn't contain some attributes
             if node.has_attributes(&invalid_attrs) {
                 continue;
@@ -106,11 +127,15 @@ fn _ungroup_groups(root: &Node, groups: &mut Vec<Node>) {
         }
 
         if node.has_children() {
-            _ungroup_groups(&node, groups);
+            _ungroup_groups(&node, has_animation, has_scripts, groups);
         }
     }
 }
 
+fn is_animated_group(node: &Node) -> bool {
+    animation::is_animated(node) || node.children().any(|c| animation::is_animated(&c))
+}
+
 fn ungroup_group(g: &Node) {
     for (aid, attr) in g.attributes().iter_svg() {
         for child in g.children() {
@@ -169,7 +194,7 @@ fn ungroup_group(g: &Node) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use svgdom::{Document, WriteToString};
+    use svgdom::{Document, ParseOptions, WriteToString};
     use task::{group_defs, remove_empty_defs, rm_unused_defs};
 
     macro_rules! test {
@@ -476,5 +501,76 @@ b"<svg>
         <rect/>
     </g>
 </svg>
+");
+
+    test!(keep_scripted_1,
+b"<svg>
+    <g id='g1'/>
+    <g>
+        <rect onclick='alert(1)'/>
+    </g>
+    <g>
+        <rect/>
+    </g>
+</svg>",
+"<svg>
+    <g id='g1'/>
+    <g>
+        <rect onclick='alert(1)'/>
+    </g>
+    <rect/>
+</svg>
+");
+
+    #[test]
+    fn keep_scripted_2() {
+        // unresolved classes are preserved in documents with scripts
+        let mut parse_opt = ParseOptions::default();
+        parse_opt.skip_unresolved_classes = false;
+
+        let doc = Document::from_data_with_opt(b"<svg>
+    <g class='item'>
+        <rect class='shape'/>
+    </g>
+    <rect onclick='alert(1)'/>
+</svg>", &parse_opt).unwrap();
+
+        ungroup_groups(&doc);
+
+        assert_eq_text!(doc.to_string_with_opt(&write_opt_for_tests!()),
+"<svg>
+    <g class='item'>
+        <rect class='shape'/>
+    </g>
+    <rect onclick='alert(1)'/>
+</svg>
+");
+    }
+
+    test!(keep_animated_1,
+b"<svg>
+    <g opacity='0.5'>
+        <rect>
+            <animate attributeName='opacity' to='1'/>
+        </rect>
+    </g>
+    <g>
+        <g>
+            <rect/>
+        </g>
+        <animate attributeName='opacity' to='0'/>
+    </g>
+</svg>",
+"<svg>
+    <g opacity='0.5'>
+        <rect>
+            <animate attributeName='opacity' to='1'/>
+        </rect>
+    </g>
+    <g>
+        <rect/>
+        <animate attributeName='opacity' to='0'/>
+    </g>
+</svg>
 ");
 }
@@ -1,8 +1,11 @@
-#[macro_use]
 extern crate clap;
+extern crate svgcleaner;
 
 use clap::{Arg, App};
 
+use svgcleaner::cli;
+use svgcleaner::options::{Group, OptionInfo, OPTIONS};
+
 use std::cmp;
 use std::fs::File;
 use std::fs::OpenOptions;
@@ -51,8 +54,17 @@ fn main() {
             .long("outdir")
             .help("Sets path to working directory")
             .value_name("DIR"))
+        .arg(Arg::with_name("help-file")
+            .long("help-file")
+            .help("Sets path to the svgcleaner's help file (data/help.txt) to update")
+            .value_name("FILE"))
         .get_matches();
 
+    if let Some(path) = m.value_of("help-file") {
+        let mut f = File::create(path).unwrap();
+        f.write_all(cli::gen_help().as_bytes()).unwrap();
+    }
+
     let workdir = m.value_of("docdir").unwrap();
     let srcdir = Path::new(workdir).join("src").to_str().unwrap().to_owned();
 
@@ -93,46 +105,52 @@ fn main() {
         }
     }
 
-    // process rst files
-    let lines = load_lines(Path::new(&srcdir).join("order.txt").to_str().unwrap());
-    for line in lines {
-        if line.starts_with("-- ") {
+    // process rst files in the options registry order
+    let mut prev_group = None;
+    for info in OPTIONS {
+        // options from the 'Other' group are not documented
+        if info.group == Group::Other {
+            continue;
+        }
+
+        if prev_group != Some(info.group) {
+            prev_group = Some(info.group);
+
             if mode == DocMode::Cli {
                 let file = OpenOptions::new().append(true).open(&out_path).unwrap();
                 let mut out_buf = BufWriter::new(file);
 
-                let title = line.replace("-- ", "");
+                let title = info.group.title();
                 write!(out_buf, "{}\n", title).unwrap();
                 for _ in 0..title.len() {
                     out_buf.write(b"=").unwrap();
                 }
                 out_buf.write(b"\n\n").unwrap();
             }
-            continue;
         }
 
+        let line = format!("{}.rst", info.name);
         let path = Path::new(&srcdir).join(&line);
         let path_str = path.to_str().unwrap();
 
         println!("{:?}", path_str);
 
-        let doc_basename = basename(path_str);
         let data = Data {
             workdir: workdir.to_owned(),
             mode: mode.clone(),
-            img_before_path: gen_svg_path(&before_dir, doc_basename),
-            img_after_path: gen_svg_path(&after_dir, doc_basename),
+            img_before_path: gen_svg_path(&before_dir, info.name),
+            img_after_path: gen_svg_path(&after_dir, info.name),
         };
 
         if mode == DocMode::Cli {
             let file = OpenOptions::new().append(true).open(&out_path).unwrap();
             let mut out_buf = BufWriter::new(file);
-            prepare_page(path_str, &data, &mut out_buf);
+            prepare_page(path_str, info, &data, &mut out_buf);
         } else {
             let path = Path::new(&outdir).join(&line);
             let mut out_buf = BufWriter::new(File::create(path).unwrap());
 
-            prepare_page(path_str, &data, &mut out_buf);
+            prepare_page(path_str, info, &data, &mut out_buf);
         }
     }
 }
@@ -149,21 +167,44 @@ fn gen_svg_path(dir: &PathBuf, basename: &str) -> String {
     Path::new(&dir).join(filename).to_str().unwrap().to_owned()
 }
 
-fn prepare_page(page_path: &str, data: &Data, out_buf: &mut BufWriter<File>) {
+// Converts an option description into a title: 'text' -> ``text``.
+fn gen_title(info: &OptionInfo) -> String {
+    let descr = info.description;
+    let bytes = descr.as_bytes();
+    let mut title = String::new();
+    for (i, c) in descr.char_indices() {
+        let is_quote = c == '\''
+            && (i == 0 || bytes[i - 1] == b' ' || i + 1 == bytes.len() || bytes[i + 1] == b' ');
+
+        if is_quote {
+            title.push_str("``");
+        } else {
+            title.push(c);
+        }
+    }
+
+    title
+}
+
+fn prepare_page(page_path: &str, info: &OptionInfo, data: &Data, out_buf: &mut BufWriter<File>) {
     let lines = load_lines(page_path);
     let mut lines_iter = lines.iter();
 
-    if data.mode == DocMode::Gui {
-        // skip title
-        lines_iter.next();
-        lines_iter.next();
+    if data.mode == DocMode::Cli {
+        // the title is generated from the option description
+        let title = gen_title(info);
+        write!(out_buf, "{}\n", title).unwrap();
+        for _ in 0..title.len() {
+            out_buf.write(b"-").unwrap();
+        }
+        out_buf.write(b"\n").unwrap();
     }
 
     while let Some(line) = lines_iter.next() {
         if line == ".. GEN_TABLE" {
             if data.mode == DocMode::Cli {
                 // write CLI arg before table
-                write!(out_buf, "CLI argument: ``--{}``\n\n", basename(page_path)).unwrap();
+                write!(out_buf, "CLI argument: ``--{}``\n\n", info.name).unwrap();
             } else {
                 writeln!(out_buf, "|\n").unwrap();
             }
@@ -328,10 +369,6 @@ fn fill_str(text: &mut String, c: char, len: usize) {
     }
 }
 
-fn basename<'a>(path: &'a str) -> &str {
-    Path::new(path).file_stem().unwrap().to_str().unwrap()
-}
-
 fn gen_svg_file(lines: &Vec<String>, insert_xmlns_xlink: bool, col: Column, data: &Data) -> usize {
     let svg_attrs = if insert_xmlns_xlink {
         "<svg xmlns=\"http://www.w3.org/2000/svg\" \